@@ -0,0 +1,236 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shows the game view in the main window and a preview of a second sky in another window, like a
+//! sky editing tool. The render graph setup for the second window follows Bevy's own
+//! `multiple_windows` example.
+
+use bevy::prelude::*;
+use bevy::render::camera::{ActiveCameras, Camera, RenderLayers};
+use bevy::render::pass::{
+    LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+    TextureAttachment,
+};
+use bevy::render::render_graph::base::MainPass;
+use bevy::render::render_graph::{
+    CameraNode, PassNode, RenderGraph, WindowSwapChainNode, WindowTextureNode,
+};
+use bevy::render::texture::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+};
+use bevy::window::{CreateWindow, WindowId};
+use bevy_skybox_cubemap::{
+    add_skybox_pass, SkyboxBundle, SkyboxCameraTint, SkyboxMaterial, SkyboxPlugin, SkyboxRotation,
+    SkyboxTextureConversion,
+};
+
+/// Name of the camera in the preview window.
+const PREVIEW_CAMERA: &str = "preview_camera";
+/// Name of the render pass drawing the preview window.
+const PREVIEW_PASS: &str = "preview_pass";
+
+fn main() {
+    App::build()
+        .insert_resource(Msaa { samples: 4 })
+        .add_state(AppState::CreateWindow)
+        .add_plugins(DefaultPlugins)
+        .add_plugin(SkyboxPlugin)
+        .add_system_set(
+            SystemSet::on_update(AppState::CreateWindow).with_system(create_window.system()),
+        )
+        .add_system_set(SystemSet::on_update(AppState::Setup).with_system(setup.system()))
+        .run();
+}
+
+// As in Bevy's example, the second window has to exist before its render graph nodes are added.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum AppState {
+    CreateWindow,
+    Setup,
+    Done,
+}
+
+fn create_window(
+    mut app_state: ResMut<State<AppState>>,
+    mut create_window_events: EventWriter<CreateWindow>,
+) {
+    create_window_events.send(CreateWindow {
+        id: WindowId::new(),
+        descriptor: WindowDescriptor {
+            width: 400.0,
+            height: 400.0,
+            title: "sky preview".to_string(),
+            ..Default::default()
+        },
+    });
+    app_state.set(AppState::Setup).unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    mut render_graph: ResMut<RenderGraph>,
+    msaa: Res<Msaa>,
+    mut app_state: ResMut<State<AppState>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+    mut skybox_conversion: ResMut<SkyboxTextureConversion>,
+) {
+    let window_id = match windows.iter().find(|w| w.id() != WindowId::default()) {
+        Some(window) => window.id(),
+        None => return,
+    };
+
+    add_preview_pass(&mut render_graph, window_id, &msaa);
+    add_skybox_pass(&mut render_graph, PREVIEW_PASS).unwrap();
+    active_cameras.add(PREVIEW_CAMERA);
+
+    let skybox_texture = asset_server.load("labeled_skybox.png");
+    skybox_conversion.make_array(skybox_texture.clone());
+
+    // The game view, with a cube under its sky.
+    commands.spawn_bundle(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        ..Default::default()
+    });
+    commands.spawn_bundle(LightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn_bundle(SkyboxBundle::new(
+        skyboxes.add(SkyboxMaterial::from_texture(skybox_texture.clone())),
+    ));
+
+    // The preview shows only its own sky, kept apart from the game view with render layers, and
+    // darkened from its camera with a tint.
+    let preview_layer = RenderLayers::layer(1);
+    commands
+        .spawn_bundle(PerspectiveCameraBundle {
+            camera: Camera {
+                name: Some(PREVIEW_CAMERA.to_string()),
+                window: window_id,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(preview_layer)
+        .insert(SkyboxCameraTint::new(Color::rgb(0.6, 0.6, 0.8)));
+    commands
+        .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial {
+            color: Color::rgb(1.0, 0.8, 0.6),
+            ..SkyboxMaterial::from_texture(skybox_texture)
+        })))
+        .insert(preview_layer)
+        .insert(SkyboxRotation::new(Vec3::Y, 0.2));
+
+    app_state.set(AppState::Done).unwrap();
+}
+
+/// Adds the swap chain, depth texture, camera, and pass nodes drawing the preview window.
+fn add_preview_pass(render_graph: &mut RenderGraph, window_id: WindowId, msaa: &Msaa) {
+    render_graph.add_node("preview_swap_chain", WindowSwapChainNode::new(window_id));
+    render_graph.add_node(
+        "preview_depth_texture",
+        WindowTextureNode::new(
+            window_id,
+            TextureDescriptor {
+                format: TextureFormat::Depth32Float,
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+                sample_count: msaa.samples,
+                ..Default::default()
+            },
+        ),
+    );
+    render_graph.add_system_node("preview_camera_node", CameraNode::new(PREVIEW_CAMERA));
+
+    let mut pass = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![msaa.color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: msaa.samples,
+    });
+    pass.add_camera(PREVIEW_CAMERA);
+    render_graph.add_node(PREVIEW_PASS, pass);
+
+    render_graph
+        .add_slot_edge(
+            "preview_swap_chain",
+            WindowSwapChainNode::OUT_TEXTURE,
+            PREVIEW_PASS,
+            if msaa.samples > 1 {
+                "color_resolve_target"
+            } else {
+                "color_attachment"
+            },
+        )
+        .unwrap();
+    render_graph
+        .add_slot_edge(
+            "preview_depth_texture",
+            WindowTextureNode::OUT_TEXTURE,
+            PREVIEW_PASS,
+            "depth",
+        )
+        .unwrap();
+    render_graph
+        .add_node_edge("preview_camera_node", PREVIEW_PASS)
+        .unwrap();
+
+    if msaa.samples > 1 {
+        render_graph.add_node(
+            "preview_multi_sampled_color_attachment",
+            WindowTextureNode::new(
+                window_id,
+                TextureDescriptor {
+                    size: Extent3d::new(1, 1, 1),
+                    mip_level_count: 1,
+                    sample_count: msaa.samples,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::default(),
+                    usage: TextureUsage::OUTPUT_ATTACHMENT,
+                },
+            ),
+        );
+        render_graph
+            .add_slot_edge(
+                "preview_multi_sampled_color_attachment",
+                WindowTextureNode::OUT_TEXTURE,
+                PREVIEW_PASS,
+                "color_attachment",
+            )
+            .unwrap();
+    }
+}
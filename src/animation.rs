@@ -0,0 +1,231 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keyframed animation of skybox parameters.
+
+use bevy::prelude::*;
+
+use crate::SkyboxMaterial;
+
+/// Component which plays back keyframed changes to a skybox, e.g. for cutscenes.
+///
+/// Each parameter has its own track of keyframes, and tracks without keyframes leave the parameter
+/// alone. Between keyframes, values are interpolated linearly (colors in linear space, rotations
+/// with a spherical interpolation). Before the first keyframe and after the last one, the first and
+/// last values are held. The [`SkyboxPlugin`](crate::SkyboxPlugin) applies the animation to the
+/// skybox's [`SkyboxMaterial`] and `Transform`. Note that all skyboxes sharing the material will
+/// change with it.
+///
+/// The `color` track tints the skybox. Since the sky texture is multiplied by the color, colors
+/// brighter than white (for example `Color::rgb(2.0, 2.0, 2.0)`) can be used to raise its exposure.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxAnimation, SkyboxBundle};
+/// # fn setup(mut commands: Commands) {
+/// commands.spawn_bundle(SkyboxBundle::default()).insert(
+///     SkyboxAnimation::default()
+///         // Fade to a red tint over 3 seconds, then back.
+///         .with_color(0.0, Color::WHITE)
+///         .with_color(3.0, Color::RED)
+///         .with_color(6.0, Color::WHITE)
+///         // Meanwhile, blend in the second texture.
+///         .with_blend_factor(0.0, 0.0)
+///         .with_blend_factor(6.0, 1.0)
+///         // And turn the sky a quarter turn.
+///         .with_rotation(0.0, Quat::IDENTITY)
+///         .with_rotation(6.0, Quat::from_rotation_y(std::f32::consts::FRAC_PI_2)),
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxAnimation {
+    /// Keyframes for the material's `color`.
+    pub color: Vec<Keyframe<Color>>,
+    /// Keyframes for the material's `blend_factor`.
+    pub blend_factor: Vec<Keyframe<f32>>,
+    /// Keyframes for the skybox's `Transform` rotation.
+    pub rotation: Vec<Keyframe<Quat>>,
+    /// Whether to start over from the beginning after the last keyframe.
+    pub looping: bool,
+    /// Playback speed. `1.0` is normal speed, and negative speeds play backwards.
+    pub speed: f32,
+    /// While paused, the animation stays at its current time.
+    pub paused: bool,
+    /// Current time in the animation, in seconds.
+    pub time: f32,
+}
+
+impl SkyboxAnimation {
+    /// Adds a keyframe setting the color to `color` at `time` seconds.
+    pub fn with_color(mut self, time: f32, color: Color) -> Self {
+        insert_keyframe(&mut self.color, time, color);
+        self
+    }
+
+    /// Adds a keyframe setting the blend factor to `blend_factor` at `time` seconds.
+    pub fn with_blend_factor(mut self, time: f32, blend_factor: f32) -> Self {
+        insert_keyframe(&mut self.blend_factor, time, blend_factor);
+        self
+    }
+
+    /// Adds a keyframe setting the rotation to `rotation` at `time` seconds.
+    pub fn with_rotation(mut self, time: f32, rotation: Quat) -> Self {
+        insert_keyframe(&mut self.rotation, time, rotation);
+        self
+    }
+
+    /// Makes the animation start over after the last keyframe.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Time of the last keyframe on any track, in seconds.
+    pub fn duration(&self) -> f32 {
+        [
+            self.color.last().map(|k| k.time),
+            self.blend_factor.last().map(|k| k.time),
+            self.rotation.last().map(|k| k.time),
+        ]
+        .iter()
+        .flatten()
+        .fold(0.0, |a, &b| a.max(b))
+    }
+
+    /// Whether the animation has played to its end (or its start, if playing backwards). Looping
+    /// animations never finish.
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && if self.speed < 0.0 {
+                self.time <= 0.0
+            } else {
+                self.time >= self.duration()
+            }
+    }
+
+    /// Color at the current time, if the color track has any keyframes.
+    pub fn current_color(&self) -> Option<Color> {
+        sample(&self.color, self.time, |a, b, t| {
+            let a = Vec4::from(a.as_linear_rgba_f32());
+            let b = Vec4::from(b.as_linear_rgba_f32());
+            let [r, g, b, a] = a.lerp(b, t).into();
+            Color::rgba_linear(r, g, b, a)
+        })
+    }
+
+    /// Blend factor at the current time, if the blend factor track has any keyframes.
+    pub fn current_blend_factor(&self) -> Option<f32> {
+        sample(&self.blend_factor, self.time, |a, b, t| a + (b - a) * t)
+    }
+
+    /// Rotation at the current time, if the rotation track has any keyframes.
+    pub fn current_rotation(&self) -> Option<Quat> {
+        sample(&self.rotation, self.time, |a, b, t| a.slerp(*b, t))
+    }
+}
+
+impl Default for SkyboxAnimation {
+    /// An empty animation which plays once at normal speed.
+    fn default() -> Self {
+        Self {
+            color: Vec::new(),
+            blend_factor: Vec::new(),
+            rotation: Vec::new(),
+            looping: false,
+            speed: 1.0,
+            paused: false,
+            time: 0.0,
+        }
+    }
+}
+
+/// Value of a parameter at a point in a [`SkyboxAnimation`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframe<T> {
+    /// Time of the keyframe, in seconds from the start of the animation.
+    pub time: f32,
+    /// Value of the parameter at that time.
+    pub value: T,
+}
+
+/// Inserts a keyframe, keeping the track sorted by time.
+fn insert_keyframe<T>(track: &mut Vec<Keyframe<T>>, time: f32, value: T) {
+    let index = track
+        .iter()
+        .position(|k| k.time > time)
+        .unwrap_or(track.len());
+    track.insert(index, Keyframe { time, value });
+}
+
+/// Samples a track at `time` using `lerp` to interpolate between keyframes.
+fn sample<T: Clone>(
+    track: &[Keyframe<T>],
+    time: f32,
+    lerp: impl Fn(&T, &T, f32) -> T,
+) -> Option<T> {
+    let next = track.iter().position(|k| k.time > time);
+    match next {
+        None => track.last().map(|k| k.value.clone()),
+        Some(0) => Some(track[0].value.clone()),
+        Some(next) => {
+            let (a, b) = (&track[next - 1], &track[next]);
+            let t = (time - a.time) / (b.time - a.time);
+            Some(lerp(&a.value, &b.value, t))
+        }
+    }
+}
+
+/// System which plays [`SkyboxAnimation`]s.
+pub(crate) fn run_skybox_animations(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut skyboxes: Query<(
+        &mut SkyboxAnimation,
+        &Handle<SkyboxMaterial>,
+        &mut Transform,
+    )>,
+) {
+    for (mut animation, handle, mut transform) in skyboxes.iter_mut() {
+        if animation.paused || animation.is_finished() {
+            continue;
+        }
+        let duration = animation.duration();
+        let time = animation.time + time.delta_seconds() * animation.speed;
+        animation.time = if animation.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        };
+
+        if let Some(rotation) = animation.current_rotation() {
+            transform.rotation = rotation;
+        }
+        let color = animation.current_color();
+        let blend_factor = animation.current_blend_factor();
+        if color.is_none() && blend_factor.is_none() {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            if let Some(color) = color {
+                material.color = color;
+            }
+            if let Some(blend_factor) = blend_factor {
+                material.blend_factor = blend_factor;
+            }
+        }
+    }
+}
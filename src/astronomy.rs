@@ -0,0 +1,739 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Astronomically accurate sun and moon directions.
+//!
+//! The sun's position is computed with the low-precision formula from the Astronomical Almanac,
+//! and the moon's with the largest terms of the series from Meeus' *Astronomical Algorithms*. Both
+//! are accurate to within a few hundredths of a degree for dates within a few centuries of 2000,
+//! which is good enough to show eclipses at about the right time. The moon's position is corrected
+//! for the observer standing on the surface of the Earth rather than at its center, which moves it
+//! by up to a degree.
+//!
+//! Directions are returned in world space using the same convention as the rest of the crate:
+//! +Y is up, +X is east, and -Z (Bevy's forward direction) is north.
+//!
+//! ```
+//! use bevy_skybox_cubemap::astronomy::{julian_day, sun_direction};
+//!
+//! // Noon UTC on the June solstice, at 0 degrees latitude and longitude.
+//! let sun = sun_direction(julian_day(2021, 6, 21, 12.0), 0.0, 0.0);
+//! // The sun is high in the sky, towards the north.
+//! assert!(sun.y > 0.9);
+//! assert!(sun.z < 0.0);
+//! ```
+
+use std::f64::consts::{PI, TAU};
+
+use bevy::math::{DVec3, Mat3, Quat, Vec3};
+
+/// Julian day of 2000-01-01 12:00 UTC, the epoch of the formulas used here.
+const J2000: f64 = 2451545.0;
+
+/// Place on Earth and local calendar date to compute accurate sun and moon positions for. Used by
+/// [`TimeOfDay`](crate::TimeOfDay) when set as its `observer`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observer {
+    /// Latitude in degrees. Positive is north of the equator.
+    pub latitude: f64,
+    /// Longitude in degrees. Positive is east of Greenwich.
+    pub longitude: f64,
+    /// Year of the local date.
+    pub year: i32,
+    /// Month of the local date, from 1 to 12.
+    pub month: u32,
+    /// Day of the month of the local date, from 1 to 31.
+    pub day: u32,
+    /// Offset of local time from UTC, in hours. For example, US Eastern Standard Time is `-5.0`.
+    pub utc_offset: f64,
+}
+
+impl Observer {
+    /// Julian day at `hour` local time on this observer's date.
+    pub fn julian_day(&self, hour: f64) -> f64 {
+        julian_day(self.year, self.month, self.day, hour - self.utc_offset)
+    }
+
+    /// Moves this observer's date forward by `days` (or backwards if negative).
+    pub fn advance_days(&mut self, days: i64) {
+        let (year, month, day, _) =
+            calendar_date(julian_day(self.year, self.month, self.day, 12.0) + days as f64);
+        self.year = year;
+        self.month = month;
+        self.day = day;
+    }
+
+    /// Direction towards the sun at `hour` local time.
+    pub fn sun_direction(&self, hour: f64) -> Vec3 {
+        sun_direction(self.julian_day(hour), self.latitude, self.longitude)
+    }
+
+    /// Direction towards the moon at `hour` local time.
+    pub fn moon_direction(&self, hour: f64) -> Vec3 {
+        moon_direction(self.julian_day(hour), self.latitude, self.longitude)
+    }
+
+    /// Solar eclipse seen by this observer at `hour` local time.
+    pub fn solar_eclipse(&self, hour: f64) -> Eclipse {
+        let julian_day = self.julian_day(hour);
+        solar_eclipse(
+            self.sun_direction(hour),
+            sun_angular_radius(julian_day) as f32,
+            self.moon_direction(hour),
+            moon_angular_radius(julian_day) as f32,
+        )
+    }
+
+    /// Direction towards the planet with the given orbital `elements` at `hour` local time.
+    pub fn planet_direction(&self, elements: &OrbitalElements, hour: f64) -> Vec3 {
+        planet_direction(
+            elements,
+            self.julian_day(hour),
+            self.latitude,
+            self.longitude,
+        )
+    }
+
+    /// Rotation of the stars at `hour` local time. See [`sky_rotation`].
+    pub fn sky_rotation(&self, hour: f64) -> Quat {
+        sky_rotation(self.julian_day(hour), self.latitude, self.longitude)
+    }
+}
+
+/// Converts a date and time in UTC on the Gregorian calendar to a Julian day. `hour` may be outside
+/// of `[0, 24)`, in which case it spills over into neighboring days.
+pub fn julian_day(year: i32, month: u32, day: u32, hour: f64) -> f64 {
+    let (mut year, mut month) = (year as f64, month as f64);
+    if month <= 2.0 {
+        year -= 1.0;
+        month += 12.0;
+    }
+    let century = (year / 100.0).floor();
+    let leap_correction = 2.0 - century + (century / 4.0).floor();
+    (365.25 * (year + 4716.0)).floor()
+        + (30.6001 * (month + 1.0)).floor()
+        + day as f64
+        + leap_correction
+        - 1524.5
+        + hour / 24.0
+}
+
+/// Converts a Julian day back to a Gregorian date and time in UTC, as `(year, month, day, hour)`.
+pub fn calendar_date(julian_day: f64) -> (i32, u32, u32, f64) {
+    let shifted = julian_day + 0.5;
+    let whole = shifted.floor();
+    let fraction = shifted - whole;
+    let a = if whole < 2299161.0 {
+        whole
+    } else {
+        let alpha = ((whole - 1867216.25) / 36524.25).floor();
+        whole + 1.0 + alpha - (alpha / 4.0).floor()
+    };
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+    let day = b - d - (30.6001 * e).floor();
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 };
+    let year = if month > 2.0 { c - 4716.0 } else { c - 4715.0 };
+    (year as i32, month as u32, day as u32, fraction * 24.0)
+}
+
+/// Direction towards the sun at the given Julian day (see [`julian_day`]) for an observer at
+/// `latitude` and `longitude` in degrees.
+pub fn sun_direction(julian_day: f64, latitude: f64, longitude: f64) -> Vec3 {
+    let days = julian_day - J2000;
+    let mean_longitude = 280.460 + 0.9856474 * days;
+    let mean_anomaly = (357.528 + 0.9856003 * days).to_radians();
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+            .to_radians();
+    let ecliptic = ecliptic_to_equatorial(days, ecliptic_longitude, 0.0);
+    equatorial_to_world(days, ecliptic, latitude, longitude)
+}
+
+/// Direction towards the moon at the given Julian day (see [`julian_day`]) for an observer at
+/// `latitude` and `longitude` in degrees.
+pub fn moon_direction(julian_day: f64, latitude: f64, longitude: f64) -> Vec3 {
+    let (ecliptic_longitude, ecliptic_latitude, distance) = moon_position(julian_day);
+    let equatorial =
+        ecliptic_to_equatorial(julian_day - J2000, ecliptic_longitude, ecliptic_latitude);
+    let geocentric = equatorial_to_world(julian_day - J2000, equatorial, latitude, longitude);
+    // Seen from the surface rather than the center of the Earth, the moon is shifted away from
+    // straight up by its parallax: the angle the Earth's radius covers at the moon's distance.
+    (geocentric - Vec3::Y * (EARTH_RADIUS / distance) as f32).normalize()
+}
+
+/// Equatorial radius of the Earth in kilometers.
+const EARTH_RADIUS: f64 = 6378.14;
+/// Radius of the moon in kilometers.
+const MOON_RADIUS: f64 = 1737.4;
+
+/// Terms of the series for the moon's ecliptic longitude (in millionths of a degree) and distance
+/// (in meters), as multiples of the mean elongation, the sun's mean anomaly, the moon's mean
+/// anomaly, and the moon's argument of latitude. These are the largest terms from chapter 47 of
+/// Meeus' *Astronomical Algorithms*.
+const MOON_LONGITUDE_DISTANCE_TERMS: [(f64, f64, f64, f64, f64, f64); 27] = [
+    (0.0, 0.0, 1.0, 0.0, 6288774.0, -20905355.0),
+    (2.0, 0.0, -1.0, 0.0, 1274027.0, -3699111.0),
+    (2.0, 0.0, 0.0, 0.0, 658314.0, -2955968.0),
+    (0.0, 0.0, 2.0, 0.0, 213618.0, -569925.0),
+    (0.0, 1.0, 0.0, 0.0, -185116.0, 48888.0),
+    (0.0, 0.0, 0.0, 2.0, -114332.0, -3149.0),
+    (2.0, 0.0, -2.0, 0.0, 58793.0, 246158.0),
+    (2.0, -1.0, -1.0, 0.0, 57066.0, -152138.0),
+    (2.0, 0.0, 1.0, 0.0, 53322.0, -170733.0),
+    (2.0, -1.0, 0.0, 0.0, 45758.0, -204586.0),
+    (0.0, 1.0, -1.0, 0.0, -40923.0, -129620.0),
+    (1.0, 0.0, 0.0, 0.0, -34720.0, 108743.0),
+    (0.0, 1.0, 1.0, 0.0, -30383.0, 104755.0),
+    (2.0, 0.0, 0.0, -2.0, 15327.0, 10321.0),
+    (0.0, 0.0, 1.0, 2.0, -12528.0, 0.0),
+    (0.0, 0.0, 1.0, -2.0, 10980.0, 79661.0),
+    (4.0, 0.0, -1.0, 0.0, 10675.0, -34782.0),
+    (0.0, 0.0, 3.0, 0.0, 10034.0, -23210.0),
+    (4.0, 0.0, -2.0, 0.0, 8548.0, -21636.0),
+    (2.0, 1.0, -1.0, 0.0, -7888.0, 24208.0),
+    (2.0, 1.0, 0.0, 0.0, -6766.0, 30824.0),
+    (1.0, 0.0, -1.0, 0.0, -5163.0, -8379.0),
+    (1.0, 1.0, 0.0, 0.0, 4987.0, -16675.0),
+    (2.0, -1.0, 1.0, 0.0, 4036.0, -12831.0),
+    (2.0, 0.0, 2.0, 0.0, 3994.0, -10445.0),
+    (4.0, 0.0, 0.0, 0.0, 3861.0, -11650.0),
+    (2.0, 0.0, -3.0, 0.0, 3665.0, 14403.0),
+];
+
+/// Terms of the series for the moon's ecliptic latitude, in millionths of a degree, like
+/// [`MOON_LONGITUDE_DISTANCE_TERMS`].
+const MOON_LATITUDE_TERMS: [(f64, f64, f64, f64, f64); 13] = [
+    (0.0, 0.0, 0.0, 1.0, 5128122.0),
+    (0.0, 0.0, 1.0, 1.0, 280602.0),
+    (0.0, 0.0, 1.0, -1.0, 277693.0),
+    (2.0, 0.0, 0.0, -1.0, 173237.0),
+    (2.0, 0.0, -1.0, 1.0, 55413.0),
+    (2.0, 0.0, -1.0, -1.0, 46271.0),
+    (2.0, 0.0, 0.0, 1.0, 32573.0),
+    (0.0, 0.0, 2.0, 1.0, 17198.0),
+    (2.0, 0.0, 1.0, -1.0, 9266.0),
+    (0.0, 0.0, 2.0, -1.0, 8822.0),
+    (2.0, -1.0, 0.0, -1.0, 8216.0),
+    (2.0, 0.0, -2.0, -1.0, 4324.0),
+    (2.0, 0.0, 1.0, 1.0, 4200.0),
+];
+
+/// Ecliptic longitude and latitude of the moon in radians and its distance from the center of the
+/// Earth in kilometers at the given Julian day.
+fn moon_position(julian_day: f64) -> (f64, f64, f64) {
+    // The series is in terms of dynamical time, which currently runs about 69 seconds ahead of
+    // UTC. The moon moves far enough in that time to matter for eclipses; the sun doesn't.
+    const DELTA_T_DAYS: f64 = 69.0 / 86400.0;
+    let t = (julian_day + DELTA_T_DAYS - J2000) / 36525.0;
+    let mean_longitude = 218.3164477 + 481267.88123421 * t;
+    let elongation = (297.8501921 + 445267.1114034 * t).to_radians();
+    let sun_anomaly = (357.5291092 + 35999.0502909 * t).to_radians();
+    let moon_anomaly = (134.9633964 + 477198.8675055 * t).to_radians();
+    let argument_of_latitude = (93.2720950 + 483202.0175233 * t).to_radians();
+    // Terms depending on the sun's anomaly shrink as the Earth's orbit becomes more circular.
+    let eccentricity = 1.0 - 0.002516 * t;
+    let argument = |d: f64, m: f64, mp: f64, f: f64| {
+        let angle = d * elongation + m * sun_anomaly + mp * moon_anomaly + f * argument_of_latitude;
+        (angle, eccentricity.powi(m.abs() as i32))
+    };
+
+    let (mut longitude, mut distance) = (0.0, 0.0);
+    for &(d, m, mp, f, l, r) in &MOON_LONGITUDE_DISTANCE_TERMS {
+        let (angle, e) = argument(d, m, mp, f);
+        longitude += l * e * angle.sin();
+        distance += r * e * angle.cos();
+    }
+    let mut latitude = 0.0;
+    for &(d, m, mp, f, b) in &MOON_LATITUDE_TERMS {
+        let (angle, e) = argument(d, m, mp, f);
+        latitude += b * e * angle.sin();
+    }
+    // Perturbations by Venus and Jupiter, and from the flattening of the Earth.
+    let a1 = (119.75 + 131.849 * t).to_radians();
+    let a2 = (53.09 + 479264.290 * t).to_radians();
+    let a3 = (313.45 + 481266.484 * t).to_radians();
+    let l = mean_longitude.to_radians();
+    longitude += 3958.0 * a1.sin() + 1962.0 * (l - argument_of_latitude).sin() + 318.0 * a2.sin();
+    latitude += -2235.0 * l.sin()
+        + 382.0 * a3.sin()
+        + 175.0 * (a1 - argument_of_latitude).sin()
+        + 175.0 * (a1 + argument_of_latitude).sin()
+        + 127.0 * (l - moon_anomaly).sin()
+        - 115.0 * (l + moon_anomaly).sin();
+
+    (
+        (mean_longitude + longitude / 1e6).to_radians(),
+        (latitude / 1e6).to_radians(),
+        385000.56 + distance / 1000.0,
+    )
+}
+
+/// Apparent angular radius of the sun at the given Julian day, in radians. This varies by a few
+/// percent over the year as the Earth's distance from the sun changes.
+pub fn sun_angular_radius(julian_day: f64) -> f64 {
+    let mean_anomaly = (357.528 + 0.9856003 * (julian_day - J2000)).to_radians();
+    let distance = 1.00014 - 0.01671 * mean_anomaly.cos() - 0.00014 * (2.0 * mean_anomaly).cos();
+    (0.2666 / distance).to_radians()
+}
+
+/// Apparent angular radius of the moon at the given Julian day, in radians. The moon's distance
+/// varies a lot more than the sun's, which is why some solar eclipses are total and others are
+/// annular.
+pub fn moon_angular_radius(julian_day: f64) -> f64 {
+    let (_, _, distance) = moon_position(julian_day);
+    (MOON_RADIUS / distance).asin()
+}
+
+/// How the moon is covering the sun, as computed by [`solar_eclipse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EclipseKind {
+    /// The moon isn't in front of the sun.
+    None,
+    /// The moon covers part of the sun.
+    Partial,
+    /// The moon is entirely in front of the sun, but too small to cover it, leaving a ring.
+    Annular,
+    /// The moon covers the sun completely.
+    Total,
+}
+
+/// Solar eclipse seen by an observer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eclipse {
+    /// Kind of eclipse.
+    pub kind: EclipseKind,
+    /// Fraction of the sun's disc which is covered, from `0.0` to `1.0`.
+    pub coverage: f32,
+}
+
+impl Eclipse {
+    /// Fraction of the sun's light which still reaches the observer. Useful for dimming lights
+    /// and the sky during an eclipse.
+    pub fn sunlight(&self) -> f32 {
+        1.0 - self.coverage
+    }
+}
+
+impl Default for Eclipse {
+    fn default() -> Self {
+        Self {
+            kind: EclipseKind::None,
+            coverage: 0.0,
+        }
+    }
+}
+
+/// Computes how much the moon covers the sun given their directions and angular radii in radians.
+pub fn solar_eclipse(
+    sun_direction: Vec3,
+    sun_radius: f32,
+    moon_direction: Vec3,
+    moon_radius: f32,
+) -> Eclipse {
+    let distance = sun_direction.angle_between(moon_direction);
+    let (sun, moon) = (sun_radius, moon_radius);
+    let (kind, coverage) = if distance >= sun + moon {
+        (EclipseKind::None, 0.0)
+    } else if distance <= (moon - sun).abs() {
+        if moon >= sun {
+            (EclipseKind::Total, 1.0)
+        } else {
+            (EclipseKind::Annular, (moon * moon) / (sun * sun))
+        }
+    } else {
+        // Area of the intersection of the two discs, which are small enough to treat as flat.
+        let d = distance;
+        let sun_part = sun * sun * ((d * d + sun * sun - moon * moon) / (2.0 * d * sun)).acos();
+        let moon_part = moon * moon * ((d * d + moon * moon - sun * sun) / (2.0 * d * moon)).acos();
+        let kite = 0.5
+            * ((-d + sun + moon) * (d + sun - moon) * (d - sun + moon) * (d + sun + moon))
+                .max(0.0)
+                .sqrt();
+        let overlap = sun_part + moon_part - kite;
+        (
+            EclipseKind::Partial,
+            (overlap / (std::f32::consts::PI * sun * sun)).clamp(0.0, 1.0),
+        )
+    };
+    Eclipse { kind, coverage }
+}
+
+/// Keplerian orbital elements of a planet orbiting the sun, relative to the mean ecliptic and
+/// equinox of J2000. Constants are provided for the planets visible with the naked eye, using the
+/// approximate elements published by JPL, which are good to within a fraction of a degree between
+/// 1800 and 2050.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrbitalElements {
+    /// Semi-major axis in astronomical units.
+    pub semi_major_axis: f64,
+    /// Eccentricity of the orbit.
+    pub eccentricity: f64,
+    /// Inclination of the orbit to the ecliptic, in degrees.
+    pub inclination: f64,
+    /// Mean longitude at J2000, in degrees.
+    pub mean_longitude: f64,
+    /// How fast the mean longitude increases, in degrees per Julian century (36525 days).
+    pub mean_longitude_rate: f64,
+    /// Longitude of perihelion, in degrees.
+    pub longitude_of_perihelion: f64,
+    /// Longitude of the ascending node, in degrees.
+    pub longitude_of_ascending_node: f64,
+}
+
+impl OrbitalElements {
+    /// Mercury, from JPL's approximate elements for 1800 to 2050 at the J2000 epoch.
+    pub const MERCURY: Self = Self {
+        semi_major_axis: 0.38709927,
+        eccentricity: 0.20563593,
+        inclination: 7.00497902,
+        mean_longitude: 252.25032350,
+        mean_longitude_rate: 149472.67411175,
+        longitude_of_perihelion: 77.45779628,
+        longitude_of_ascending_node: 48.33076593,
+    };
+    /// Venus, from JPL's approximate elements for 1800 to 2050 at the J2000 epoch.
+    pub const VENUS: Self = Self {
+        semi_major_axis: 0.72333566,
+        eccentricity: 0.00677672,
+        inclination: 3.39467605,
+        mean_longitude: 181.97909950,
+        mean_longitude_rate: 58517.81538729,
+        longitude_of_perihelion: 131.60246718,
+        longitude_of_ascending_node: 76.67984255,
+    };
+    /// The Earth-Moon barycenter, from JPL's approximate elements for 1800 to 2050 at the J2000
+    /// epoch. This is used as the position of the observer when computing the directions of the
+    /// other planets.
+    pub const EARTH: Self = Self {
+        semi_major_axis: 1.00000261,
+        eccentricity: 0.01671123,
+        inclination: -0.00001531,
+        mean_longitude: 100.46457166,
+        mean_longitude_rate: 35999.37244981,
+        longitude_of_perihelion: 102.93768193,
+        longitude_of_ascending_node: 0.0,
+    };
+    /// Mars, from JPL's approximate elements for 1800 to 2050 at the J2000 epoch.
+    pub const MARS: Self = Self {
+        semi_major_axis: 1.52371034,
+        eccentricity: 0.09339410,
+        inclination: 1.84969142,
+        mean_longitude: -4.55343205,
+        mean_longitude_rate: 19140.30268499,
+        longitude_of_perihelion: -23.94362959,
+        longitude_of_ascending_node: 49.55953891,
+    };
+    /// Jupiter, from JPL's approximate elements for 1800 to 2050 at the J2000 epoch.
+    pub const JUPITER: Self = Self {
+        semi_major_axis: 5.20288700,
+        eccentricity: 0.04838624,
+        inclination: 1.30439695,
+        mean_longitude: 34.39644051,
+        mean_longitude_rate: 3034.74612775,
+        longitude_of_perihelion: 14.72847983,
+        longitude_of_ascending_node: 100.47390909,
+    };
+    /// Saturn, from JPL's approximate elements for 1800 to 2050 at the J2000 epoch.
+    pub const SATURN: Self = Self {
+        semi_major_axis: 9.53667594,
+        eccentricity: 0.05386179,
+        inclination: 2.48599187,
+        mean_longitude: 49.95424423,
+        mean_longitude_rate: 1222.49362201,
+        longitude_of_perihelion: 92.59887831,
+        longitude_of_ascending_node: 113.66242448,
+    };
+
+    /// Position of the planet relative to the sun at the given Julian day, in astronomical units,
+    /// in ecliptic coordinates (X towards the vernal equinox, Z towards the north ecliptic pole).
+    pub fn heliocentric_position(&self, julian_day: f64) -> DVec3 {
+        let centuries = (julian_day - J2000) / 36525.0;
+        let mean_longitude = self.mean_longitude + self.mean_longitude_rate * centuries;
+        let mean_anomaly = (mean_longitude - self.longitude_of_perihelion)
+            .rem_euclid(360.0)
+            .to_radians();
+        let e = self.eccentricity;
+        // Solve Kepler's equation for the eccentric anomaly with a few steps of Newton's method.
+        let mut eccentric_anomaly = mean_anomaly + e * mean_anomaly.sin();
+        for _ in 0..5 {
+            eccentric_anomaly -= (eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly)
+                / (1.0 - e * eccentric_anomaly.cos());
+        }
+        let x = self.semi_major_axis * (eccentric_anomaly.cos() - e);
+        let y = self.semi_major_axis * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+        let perihelion =
+            (self.longitude_of_perihelion - self.longitude_of_ascending_node).to_radians();
+        let (sin_w, cos_w) = perihelion.sin_cos();
+        let (sin_node, cos_node) = self.longitude_of_ascending_node.to_radians().sin_cos();
+        let (sin_i, cos_i) = self.inclination.to_radians().sin_cos();
+        DVec3::new(
+            (cos_w * cos_node - sin_w * sin_node * cos_i) * x
+                + (-sin_w * cos_node - cos_w * sin_node * cos_i) * y,
+            (cos_w * sin_node + sin_w * cos_node * cos_i) * x
+                + (-sin_w * sin_node + cos_w * cos_node * cos_i) * y,
+            sin_w * sin_i * x + cos_w * sin_i * y,
+        )
+    }
+}
+
+/// Direction towards the planet with the given orbital `elements` at the given Julian day (see
+/// [`julian_day`]) for an observer at `latitude` and `longitude` in degrees.
+pub fn planet_direction(
+    elements: &OrbitalElements,
+    julian_day: f64,
+    latitude: f64,
+    longitude: f64,
+) -> Vec3 {
+    let days = julian_day - J2000;
+    let ecliptic = (elements.heliocentric_position(julian_day)
+        - OrbitalElements::EARTH.heliocentric_position(julian_day))
+    .normalize();
+    let obliquity = (23.439 - 0.0000004 * days).to_radians();
+    let (sin_obliquity, cos_obliquity) = obliquity.sin_cos();
+    let equatorial = DVec3::new(
+        ecliptic.x,
+        cos_obliquity * ecliptic.y - sin_obliquity * ecliptic.z,
+        sin_obliquity * ecliptic.y + cos_obliquity * ecliptic.z,
+    );
+    equatorial_to_world(days, equatorial, latitude, longitude)
+}
+
+/// Rotation from equatorial coordinates (X towards the vernal equinox, Z towards the north celestial
+/// pole) to world space for an observer at `latitude` and `longitude` in degrees at the given Julian
+/// day. This is how far the stars have turned overhead, so rotating a skybox baked in equatorial
+/// coordinates (like one from [`StarCubemap`](crate::StarCubemap)) by it puts the stars where they
+/// really are.
+pub fn sky_rotation(julian_day: f64, latitude: f64, longitude: f64) -> Quat {
+    let days = julian_day - J2000;
+    let axis = |axis| equatorial_to_world(days, axis, latitude, longitude);
+    Quat::from_rotation_mat3(&Mat3::from_cols(
+        axis(DVec3::X),
+        axis(DVec3::Y),
+        axis(DVec3::Z),
+    ))
+}
+
+/// Rotation from a star system's ecliptic coordinates (X towards the planet's vernal equinox, Z
+/// towards the north pole of its orbit) to world space, for an observer at `latitude` in degrees on
+/// a made-up planet. This is [`sky_rotation`] for planets other than Earth, such as in a space
+/// game, so a skybox baked in the star system's coordinates shows the stars turning overhead
+/// through the planet's day and shifting with its seasons.
+///
+/// The planet spins once every `day_length` from noon to noon, and orbits its star once every
+/// `year_length`, both in the same units as `time`, with its axis tilted `axial_tilt` degrees from
+/// the pole of its orbit. `time` counts from midnight at the observer's longitude on the vernal
+/// equinox, so `time % day_length` is the time of day. The planet is assumed to spin the same way
+/// it orbits, as Earth does.
+///
+/// ```
+/// use bevy::math::Vec3;
+/// use bevy_skybox_cubemap::astronomy::planet_sky_rotation;
+///
+/// // At the north pole, the pole of the orbit is as far from the zenith as the axis is tilted,
+/// // throughout the day.
+/// for hour in 0..24 {
+///     let rotation = planet_sky_rotation(hour as f64, 24.0, 24.0 * 365.25, 23.44, 90.0);
+///     let orbit_pole = rotation * Vec3::Z;
+///     assert!((orbit_pole.y - 23.44f32.to_radians().cos()).abs() < 1e-4);
+/// }
+/// ```
+pub fn planet_sky_rotation(
+    time: f64,
+    day_length: f64,
+    year_length: f64,
+    axial_tilt: f64,
+    latitude: f64,
+) -> Quat {
+    // The stars come back around a little sooner than the star does, since the planet has moved
+    // along its orbit in the meantime.
+    let sidereal_day = day_length * year_length / (day_length + year_length);
+    // At midnight on the vernal equinox, the star is opposite the meridian.
+    let sidereal_time = PI + TAU * time / sidereal_day;
+    let (sin_tilt, cos_tilt) = axial_tilt.to_radians().sin_cos();
+    let axis = |ecliptic: DVec3| {
+        let equatorial = DVec3::new(
+            ecliptic.x,
+            cos_tilt * ecliptic.y - sin_tilt * ecliptic.z,
+            sin_tilt * ecliptic.y + cos_tilt * ecliptic.z,
+        );
+        equatorial_to_horizon(equatorial, sidereal_time, latitude)
+    };
+    Quat::from_rotation_mat3(&Mat3::from_cols(
+        axis(DVec3::X),
+        axis(DVec3::Y),
+        axis(DVec3::Z),
+    ))
+}
+
+/// Converts ecliptic longitude and latitude (in radians) to a unit vector in equatorial coordinates
+/// (X towards the vernal equinox, Z towards the north celestial pole).
+fn ecliptic_to_equatorial(days: f64, longitude: f64, latitude: f64) -> DVec3 {
+    let obliquity = (23.439 - 0.0000004 * days).to_radians();
+    let (sin_obliquity, cos_obliquity) = obliquity.sin_cos();
+    let (sin_lon, cos_lon) = longitude.sin_cos();
+    let (sin_lat, cos_lat) = latitude.sin_cos();
+    DVec3::new(
+        cos_lat * cos_lon,
+        cos_obliquity * cos_lat * sin_lon - sin_obliquity * sin_lat,
+        sin_obliquity * cos_lat * sin_lon + cos_obliquity * sin_lat,
+    )
+}
+
+/// Converts a unit vector in equatorial coordinates to a world-space direction in the sky of an
+/// observer at `latitude` and `longitude` (in degrees).
+fn equatorial_to_world(days: f64, equatorial: DVec3, latitude: f64, longitude: f64) -> Vec3 {
+    // Greenwich mean sidereal time, in degrees.
+    let sidereal_time = 280.46061837 + 360.98564736629 * days;
+    equatorial_to_horizon(
+        equatorial,
+        (sidereal_time + longitude).to_radians(),
+        latitude,
+    )
+}
+
+/// Converts a unit vector in equatorial coordinates to a world-space direction in the sky of an
+/// observer at `latitude` (in degrees) at the given local sidereal time (in radians).
+fn equatorial_to_horizon(equatorial: DVec3, sidereal_time: f64, latitude: f64) -> Vec3 {
+    let right_ascension = equatorial.y.atan2(equatorial.x);
+    let declination = equatorial.z.clamp(-1.0, 1.0).asin();
+    let hour_angle = sidereal_time - right_ascension;
+
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_dec, cos_dec) = declination.sin_cos();
+    let (sin_ha, cos_ha) = hour_angle.sin_cos();
+    let east = -cos_dec * sin_ha;
+    let north = cos_lat * sin_dec - sin_lat * cos_dec * cos_ha;
+    let up = sin_lat * sin_dec + cos_lat * cos_dec * cos_ha;
+    Vec3::new(east as f32, up as f32, -north as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Angle between two directions in degrees, computed precisely enough for small angles.
+    fn angle_between(a: Vec3, b: Vec3) -> f64 {
+        let (a, b) = (a.as_f64(), b.as_f64());
+        a.cross(b).length().atan2(a.dot(b)).to_degrees()
+    }
+
+    /// Direction the sun or moon should be in given its right ascension and declination in
+    /// degrees.
+    fn equatorial_direction(julian_day: f64, right_ascension: f64, declination: f64) -> Vec3 {
+        let (sin_ra, cos_ra) = right_ascension.to_radians().sin_cos();
+        let (sin_dec, cos_dec) = declination.to_radians().sin_cos();
+        let equatorial = DVec3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec);
+        equatorial_to_world(julian_day - J2000, equatorial, 40.0, -75.0)
+    }
+
+    #[test]
+    fn julian_days_match_meeus() {
+        // Examples 7.a and the table after 7.b in Meeus' Astronomical Algorithms.
+        let cases = [
+            ((1957, 10, 4, 0.81 * 24.0), 2436116.31),
+            ((2000, 1, 1, 12.0), 2451545.0),
+            ((1987, 1, 27, 0.0), 2446822.5),
+            ((1987, 6, 19, 12.0), 2446966.0),
+            ((1988, 1, 27, 0.0), 2447187.5),
+            ((1900, 1, 1, 0.0), 2415020.5),
+            ((1600, 1, 1, 0.0), 2305447.5),
+            ((1600, 12, 31, 0.0), 2305812.5),
+        ];
+        for &((year, month, day, hour), expected) in &cases {
+            let julian_day = julian_day(year, month, day, hour);
+            assert!(
+                (julian_day - expected).abs() < 1e-6,
+                "{}-{}-{}: {} instead of {}",
+                year,
+                month,
+                day,
+                julian_day,
+                expected,
+            );
+            let (y, m, d, h) = calendar_date(julian_day);
+            assert_eq!((y, m, d), (year, month, day));
+            assert!((h - hour).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn sidereal_time_matches_meeus() {
+        // Example 12.a: at 1987-04-10 0h UT, Greenwich mean sidereal time is 13h10m46.3668s, so a
+        // point on the celestial equator at that right ascension is overhead at 0° longitude.
+        let julian_day = julian_day(1987, 4, 10, 0.0);
+        let right_ascension = (13.0 + 10.0 / 60.0 + 46.3668 / 3600.0) * 15.0_f64;
+        let (sin_ra, cos_ra) = right_ascension.to_radians().sin_cos();
+        let overhead = equatorial_to_world(
+            julian_day - J2000,
+            DVec3::new(cos_ra, sin_ra, 0.0),
+            0.0,
+            0.0,
+        );
+        assert!(angle_between(overhead, Vec3::Y) < 0.001);
+    }
+
+    #[test]
+    fn sun_direction_matches_meeus() {
+        // Example 25.a: on 1992-10-13 0h, the sun is at right ascension 13h13m31.4s and declination
+        // -7°47'06".
+        let julian_day = 2448908.5;
+        let expected = equatorial_direction(
+            julian_day,
+            (13.0 + 13.0 / 60.0 + 31.4 / 3600.0) * 15.0,
+            -(7.0 + 47.0 / 60.0 + 6.0 / 3600.0),
+        );
+        let sun = sun_direction(julian_day, 40.0, -75.0);
+        assert!(angle_between(sun, expected) < 0.01);
+    }
+
+    #[test]
+    fn moon_position_matches_meeus() {
+        // Example 47.a: at 1992-04-12 0h dynamical time, the moon's geometric ecliptic longitude
+        // is 133.162655°, its latitude -3.229126°, and its distance 368409.7 km.
+        let (longitude, latitude, distance) = moon_position(2448724.5 - 69.0 / 86400.0);
+        assert!((longitude.to_degrees().rem_euclid(360.0) - 133.162655).abs() < 0.01);
+        assert!((latitude.to_degrees() - -3.229126).abs() < 0.01);
+        // Only the largest terms of the distance series are used.
+        assert!((distance - 368409.7).abs() < 100.0);
+    }
+
+    #[test]
+    fn total_eclipse_of_2017() {
+        // Near the point of greatest eclipse, outside Hopkinsville, Kentucky, the partial phases
+        // ran from about 16:56 to 19:51 UTC, with totality around 18:25.
+        let observer = Observer {
+            latitude: 36.97,
+            longitude: -87.67,
+            year: 2017,
+            month: 8,
+            day: 21,
+            utc_offset: 0.0,
+        };
+        assert_eq!(observer.solar_eclipse(16.8).kind, EclipseKind::None);
+        let partial = observer.solar_eclipse(17.5);
+        assert_eq!(partial.kind, EclipseKind::Partial);
+        assert!(partial.coverage > 0.1 && partial.coverage < 0.5);
+        assert_eq!(observer.solar_eclipse(18.42).kind, EclipseKind::Total);
+        assert_eq!(observer.solar_eclipse(18.42).sunlight(), 0.0);
+        assert_eq!(observer.solar_eclipse(20.0).kind, EclipseKind::None);
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Changing how skyboxes look from a single camera.
+
+use bevy::core::AsBytes;
+use bevy::ecs::system::BoxedSystem;
+use bevy::prelude::*;
+use bevy::render::camera::ActiveCameras;
+use bevy::render::render_graph::{CommandQueue, Node, ResourceSlots, SystemNode};
+use bevy::render::renderer::{
+    BufferId, BufferInfo, BufferMapMode, BufferUsage, RenderContext, RenderResourceBinding,
+    RenderResourceContext,
+};
+
+use crate::SkyboxConvention;
+
+/// Component which changes how skyboxes look from the camera it's attached to, such as a green
+/// cast for a security camera's view, without duplicating the skybox's material for each camera.
+///
+/// `tint` is multiplied with everything the camera sees of the sky, including the sun, lightning,
+/// and other effects, like a filter on the camera's lens. Colors brighter than white raise the
+/// sky's exposure. If `material_color` is set, it's used in place of the
+/// [`SkyboxMaterial`](crate::SkyboxMaterial)'s `color`, and if `layer` is set, it picks which sky
+/// to show from textures holding several in place of the material's `layer`, such as each eye's
+/// sky from [`stereo_equirectangular_to_skybox`](crate::stereo_equirectangular_to_skybox). Cameras
+/// without this component see skyboxes as their materials describe them.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyboxCameraTint;
+/// # fn setup(mut commands: Commands) {
+/// commands
+///     .spawn_bundle(PerspectiveCameraBundle::default())
+///     .insert(SkyboxCameraTint::new(Color::rgb(0.4, 1.0, 0.5)));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxCameraTint {
+    /// Color multiplied with the sky as seen by this camera.
+    pub tint: Color,
+    /// Color to use instead of the material's `color` for this camera.
+    pub material_color: Option<Color>,
+    /// Sky to show instead of the material's `layer` for this camera.
+    pub layer: Option<u32>,
+}
+
+impl SkyboxCameraTint {
+    /// Creates a `SkyboxCameraTint` multiplying the sky by `tint`.
+    pub fn new(tint: Color) -> Self {
+        Self {
+            tint,
+            material_color: None,
+            layer: None,
+        }
+    }
+
+    /// Uses `color` in place of the material's `color` for this camera.
+    pub fn with_material_color(mut self, color: Color) -> Self {
+        self.material_color = Some(color);
+        self
+    }
+
+    /// Shows sky `layer` of the material's textures to this camera.
+    pub fn with_layer(mut self, layer: u32) -> Self {
+        self.layer = Some(layer);
+        self
+    }
+
+    /// Packs the tint, and the `convention` the sky textures are sampled in, for the
+    /// `SkyboxCamera` uniform block. The order must match the block in `skybox.frag`.
+    fn uniform(&self, convention: SkyboxConvention) -> [f32; CAMERA_UNIFORM_FLOATS] {
+        let (color, replace_color) = match self.material_color {
+            Some(color) => (color.as_linear_rgba_f32(), 1.0),
+            None => ([1.0; 4], 0.0),
+        };
+        let (layer, replace_layer) = match self.layer {
+            Some(layer) => (layer as f32, 1.0),
+            None => (0.0, 0.0),
+        };
+        let mut uniform = [0.0; CAMERA_UNIFORM_FLOATS];
+        uniform[0..4].copy_from_slice(&self.tint.as_linear_rgba_f32());
+        uniform[4..8].copy_from_slice(&color);
+        uniform[8..12].copy_from_slice(&[replace_color, replace_layer, layer, 0.0]);
+        let matrix = convention.matrix();
+        for (column, axis) in uniform[12..24]
+            .chunks_exact_mut(4)
+            .zip(matrix.to_cols_array_2d().iter())
+        {
+            column[..3].copy_from_slice(axis);
+        }
+        uniform
+    }
+}
+
+impl Default for SkyboxCameraTint {
+    /// Leaves the sky as the material describes it.
+    fn default() -> Self {
+        Self::new(Color::WHITE)
+    }
+}
+
+/// Name of the per-camera uniform block in the skybox shader.
+const SKYBOX_CAMERA: &str = "SkyboxCamera";
+
+/// Number of floats in the `SkyboxCamera` uniform block.
+const CAMERA_UNIFORM_FLOATS: usize = 24;
+
+/// Size of the `SkyboxCamera` uniform block in bytes.
+const CAMERA_UNIFORM_SIZE: usize = CAMERA_UNIFORM_FLOATS * std::mem::size_of::<f32>();
+
+/// Render graph node which gives every active camera the `SkyboxCamera` uniform from its
+/// [`SkyboxCameraTint`]. Every camera which draws skyboxes needs the binding, or Bevy can't build
+/// the skybox pipeline's camera bind group and skips drawing the skybox, so cameras without a tint
+/// get the default one. The [`SkyboxConvention`] goes in the same block, since it's the only other
+/// uniform every skybox shares.
+#[derive(Debug, Default)]
+pub(crate) struct SkyboxCameraTintNode {
+    command_queue: CommandQueue,
+}
+
+impl Node for SkyboxCameraTintNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        self.command_queue.execute(render_context);
+    }
+}
+
+impl SystemNode for SkyboxCameraTintNode {
+    fn get_system(&self) -> BoxedSystem {
+        let system = camera_tint_node_system.system().config(|config| {
+            config.0 = Some(CameraTintNodeState {
+                command_queue: self.command_queue.clone(),
+                staging_buffer: None,
+            })
+        });
+        Box::new(system)
+    }
+}
+
+#[derive(Debug, Default)]
+struct CameraTintNodeState {
+    command_queue: CommandQueue,
+    /// Buffer the uniforms are written to before being copied to each camera's buffer, and how many
+    /// cameras it has room for.
+    staging_buffer: Option<(BufferId, usize)>,
+}
+
+/// System which writes each active camera's `SkyboxCamera` uniform.
+fn camera_tint_node_system(
+    mut state: Local<CameraTintNodeState>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    convention: Res<SkyboxConvention>,
+    tints: Query<&SkyboxCameraTint>,
+) {
+    let render_resource_context = &**render_resource_context;
+    let cameras = active_cameras
+        .iter()
+        .filter(|camera| camera.entity.is_some())
+        .count();
+    if cameras == 0 {
+        return;
+    }
+
+    let staging_buffer = match state.staging_buffer {
+        Some((staging_buffer, capacity)) if capacity >= cameras => {
+            render_resource_context.map_buffer(staging_buffer, BufferMapMode::Write);
+            staging_buffer
+        }
+        previous => {
+            if let Some((staging_buffer, _)) = previous {
+                render_resource_context.remove_buffer(staging_buffer);
+            }
+            let staging_buffer = render_resource_context.create_buffer(BufferInfo {
+                size: CAMERA_UNIFORM_SIZE * cameras,
+                buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                mapped_at_creation: true,
+            });
+            state.staging_buffer = Some((staging_buffer, cameras));
+            staging_buffer
+        }
+    };
+
+    let default_tint = SkyboxCameraTint::default();
+    let mut offset = 0;
+    for camera in active_cameras.iter_mut() {
+        let entity = match camera.entity {
+            Some(entity) => entity,
+            None => continue,
+        };
+        if camera.bindings.get(SKYBOX_CAMERA).is_none() {
+            let buffer = render_resource_context.create_buffer(BufferInfo {
+                size: CAMERA_UNIFORM_SIZE,
+                buffer_usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
+                ..Default::default()
+            });
+            camera.bindings.set(
+                SKYBOX_CAMERA,
+                RenderResourceBinding::Buffer {
+                    buffer,
+                    range: 0..CAMERA_UNIFORM_SIZE as u64,
+                    dynamic_index: None,
+                },
+            );
+        }
+        if let Some(RenderResourceBinding::Buffer { buffer, .. }) =
+            camera.bindings.get(SKYBOX_CAMERA)
+        {
+            let uniform = tints
+                .get(entity)
+                .unwrap_or(&default_tint)
+                .uniform(*convention);
+            render_resource_context.write_mapped_buffer(
+                staging_buffer,
+                offset..(offset + CAMERA_UNIFORM_SIZE as u64),
+                &mut |data, _renderer| {
+                    data[0..CAMERA_UNIFORM_SIZE].copy_from_slice(uniform.as_bytes());
+                },
+            );
+            state.command_queue.copy_buffer_to_buffer(
+                staging_buffer,
+                offset,
+                *buffer,
+                0,
+                CAMERA_UNIFORM_SIZE as u64,
+            );
+            offset += CAMERA_UNIFORM_SIZE as u64;
+        }
+    }
+
+    render_resource_context.unmap_buffer(staging_buffer);
+}
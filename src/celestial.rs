@@ -0,0 +1,149 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Planets and other bodies drawn in the sky.
+
+use bevy::prelude::*;
+
+use crate::astronomy::OrbitalElements;
+use crate::{SkyDisc, SkyboxMaterial, TimeOfDay, MAX_SKY_DISCS};
+
+/// Component which draws planets and other small bodies in a skybox's sky.
+///
+/// The [`SkyboxPlugin`](crate::SkyboxPlugin) places each body every frame by setting the
+/// `sky_discs` of the skybox's [`SkyboxMaterial`], so all skyboxes sharing the material show the
+/// same bodies. Only the first [`MAX_SKY_DISCS`] bodies are drawn.
+///
+/// If the [`SkyboxDayNightPlugin`](crate::SkyboxDayNightPlugin) is running, bodies fade out during
+/// the day unless they are `visible_in_daylight`. Bodies following an orbit are only shown when
+/// the [`TimeOfDay`] has an [`Observer`](crate::astronomy::Observer) to compute their position
+/// for.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::astronomy::OrbitalElements;
+/// # use bevy_skybox_cubemap::{CelestialBody, SkyboxBundle, SkyboxCelestialBodies};
+/// # fn setup(mut commands: Commands) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::default())
+///     .insert(SkyboxCelestialBodies {
+///         bodies: vec![
+///             CelestialBody::orbiting(OrbitalElements::VENUS, Color::rgb(1.0, 1.0, 0.9)),
+///             CelestialBody::orbiting(OrbitalElements::MARS, Color::rgb(1.0, 0.6, 0.4)),
+///             // A space station hanging in the western sky.
+///             CelestialBody::fixed(Vec3::new(-1.0, 0.5, 0.0), Color::WHITE),
+///         ],
+///     });
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxCelestialBodies {
+    /// Bodies to draw.
+    pub bodies: Vec<CelestialBody>,
+}
+
+/// Body drawn in the sky by [`SkyboxCelestialBodies`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CelestialBody {
+    /// Where the body is in the sky.
+    pub position: CelestialPosition,
+    /// Apparent angular radius of the body, in radians. Real planets are far smaller than a pixel,
+    /// so the default is exaggerated to make them visible.
+    pub angular_radius: f32,
+    /// Color of the body. Brighter-than-white colors make it stand out more.
+    pub color: Color,
+    /// Whether the body stays visible during the day.
+    pub visible_in_daylight: bool,
+}
+
+impl CelestialBody {
+    /// Creates a body which stays in `direction` in the sky.
+    pub fn fixed(direction: Vec3, color: Color) -> Self {
+        Self {
+            position: CelestialPosition::Fixed(direction),
+            angular_radius: 0.003,
+            color,
+            visible_in_daylight: false,
+        }
+    }
+
+    /// Creates a planet which orbits the sun with the given `elements`.
+    pub fn orbiting(elements: OrbitalElements, color: Color) -> Self {
+        Self {
+            position: CelestialPosition::Orbit(elements),
+            ..Self::fixed(Vec3::Y, color)
+        }
+    }
+
+    /// World-space direction of the body, if it can be placed at the current time of day.
+    fn direction(&self, time_of_day: Option<&TimeOfDay>) -> Option<Vec3> {
+        match &self.position {
+            CelestialPosition::Fixed(direction) => Some(*direction),
+            CelestialPosition::Orbit(elements) => {
+                let time_of_day = time_of_day?;
+                let observer = time_of_day.observer.as_ref()?;
+                Some(observer.planet_direction(elements, time_of_day.hour as f64))
+            }
+        }
+    }
+}
+
+/// Where a [`CelestialBody`] is in the sky.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CelestialPosition {
+    /// Always in the same world-space direction.
+    Fixed(Vec3),
+    /// Orbiting the sun, as seen from the Earth by the [`TimeOfDay`]'s observer.
+    Orbit(OrbitalElements),
+}
+
+/// System which places [`SkyboxCelestialBodies`] in their skyboxes' materials.
+pub(crate) fn place_celestial_bodies(
+    time_of_day: Option<Res<TimeOfDay>>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    skyboxes: Query<(&SkyboxCelestialBodies, &Handle<SkyboxMaterial>)>,
+) {
+    let time_of_day = time_of_day.as_deref();
+    let night = time_of_day.map_or(1.0, |time_of_day| 1.0 - time_of_day.daylight());
+    for (bodies, handle) in skyboxes.iter() {
+        let discs: Vec<_> = bodies
+            .bodies
+            .iter()
+            .take(MAX_SKY_DISCS)
+            .filter_map(|body| {
+                let direction = body.direction(time_of_day)?;
+                let fade = if body.visible_in_daylight { 1.0 } else { night };
+                let [r, g, b, a] = body.color.as_linear_rgba_f32();
+                Some(SkyDisc {
+                    direction,
+                    angular_radius: body.angular_radius,
+                    color: Color::rgba_linear(r * fade, g * fade, b * fade, a),
+                })
+            })
+            .collect();
+
+        // Only touch the material if something changed, since getting it mutably causes it to be
+        // re-uploaded.
+        match materials.get(handle) {
+            Some(material) if material.sky_discs != discs => {}
+            _ => continue,
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.sky_discs = discs;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sampling skybox textures made for other coordinate conventions.
+
+use bevy::prelude::*;
+
+use crate::cube::{direction_to_face_uv, FACES};
+use crate::SkyboxMaterial;
+
+/// Resource with the coordinate convention skybox textures were authored in, added by the
+/// [`SkyboxPlugin`](crate::SkyboxPlugin). Bevy is Y-up and right-handed, with -Z forward, and
+/// skybox textures are normally laid out for that, as the crate docs describe. Skyboxes exported
+/// from tools with other conventions put their faces on other axes, so their sky comes out on its
+/// side or mirrored. Setting the convention to the one the textures were made in remaps which way
+/// each face of the cube points, so their layers and faces can be used as exported instead of
+/// being shuffled by hand.
+///
+/// The remapping is done when the texture is sampled, so it applies to every skybox texture
+/// however it was loaded or converted, and to [`SkyboxPicked`](crate::SkyboxPicked) events. It
+/// doesn't change anything placed in the world rather than on the texture, such as the sun,
+/// lightning, or the horizon, and images within each face are still oriented as for their axis in
+/// the crate docs.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxConvention, SkyboxPlugin};
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     .insert_resource(SkyboxConvention::ZUpRightHanded)
+///     .run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SkyboxConvention {
+    /// Y up, +X right, and -Z forward, as in Bevy and glTF.
+    YUpRightHanded,
+    /// Y up, +X right, and +Z forward, as in Unity.
+    YUpLeftHanded,
+    /// Z up, +X right, and +Y forward, as in Blender.
+    ZUpRightHanded,
+    /// Z up, +Y right, and +X forward, as in Unreal Engine.
+    ZUpLeftHanded,
+}
+
+impl SkyboxConvention {
+    /// Converts a direction in a skybox's own space, which is Y-up and right-handed like the rest
+    /// of Bevy, into the direction to look up in its textures.
+    pub fn texture_direction(self, direction: Vec3) -> Vec3 {
+        self.matrix() * direction
+    }
+
+    /// Finds where the skybox drawn with `skybox_transform` and `material` samples its texture in
+    /// the world-space `direction`, like
+    /// [`sky_texture_coordinates`](crate::sky_texture_coordinates) but for textures authored in
+    /// this convention.
+    pub fn sky_texture_coordinates(
+        self,
+        direction: Vec3,
+        skybox_transform: &GlobalTransform,
+        material: &SkyboxMaterial,
+    ) -> (u32, Vec2) {
+        let local = (skybox_transform.rotation.inverse() * direction).normalize();
+        let (face, uv) = direction_to_face_uv(
+            self.texture_direction(local + Vec3::new(0.0, material.horizon_offset, 0.0)),
+        );
+        (material.layer * FACES as u32 + face as u32, uv)
+    }
+
+    /// Matrix taking directions in Bevy's convention to directions in this one.
+    pub(crate) fn matrix(self) -> Mat3 {
+        match self {
+            SkyboxConvention::YUpRightHanded => Mat3::IDENTITY,
+            SkyboxConvention::YUpLeftHanded => Mat3::from_cols(Vec3::X, Vec3::Y, -Vec3::Z),
+            SkyboxConvention::ZUpRightHanded => Mat3::from_cols(Vec3::X, Vec3::Z, -Vec3::Y),
+            SkyboxConvention::ZUpLeftHanded => Mat3::from_cols(Vec3::Y, Vec3::Z, -Vec3::X),
+        }
+    }
+}
+
+impl Default for SkyboxConvention {
+    /// Bevy's own convention, which needs no remapping.
+    fn default() -> Self {
+        SkyboxConvention::YUpRightHanded
+    }
+}
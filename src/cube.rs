@@ -0,0 +1,202 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for building skybox textures on the CPU.
+
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, TextureDimension, TextureFormat};
+
+/// Number of layers in a skybox texture.
+pub(crate) const FACES: usize = 6;
+
+/// Finds the layer and texture coordinates that the skybox shader samples for `direction`. This
+/// mirrors `sampleCubeHacky` in `skybox.frag` and must be kept in sync with it.
+pub(crate) fn direction_to_face_uv(direction: Vec3) -> (usize, Vec2) {
+    let abs = direction.abs();
+    let (face, max_adjust, uv) = if abs.z >= abs.x && abs.z >= abs.y {
+        (
+            if direction.z < 0.0 { 5 } else { 4 },
+            0.5 / abs.z,
+            Vec2::new(direction.x * -direction.z.signum(), -direction.y),
+        )
+    } else if abs.y >= abs.x {
+        (
+            if direction.y < 0.0 { 3 } else { 2 },
+            0.5 / direction.y,
+            Vec2::new(direction.x * direction.y.signum(), -direction.z),
+        )
+    } else {
+        (
+            if direction.x < 0.0 { 1 } else { 0 },
+            0.5 / direction.x,
+            Vec2::new(direction.z, direction.y * -direction.x.signum()),
+        )
+    };
+    (face, uv * max_adjust + Vec2::splat(0.5))
+}
+
+/// Finds the direction that the skybox shader samples at `uv` on layer `face`, the inverse of
+/// [`direction_to_face_uv`]. The direction is on the surface of the unit cube, so it isn't
+/// normalized.
+pub(crate) fn face_uv_to_direction(face: usize, uv: Vec2) -> Vec3 {
+    let centered = uv * 2.0 - Vec2::ONE;
+    let (u, v) = (centered.x, centered.y);
+    match face {
+        0 => Vec3::new(1.0, -v, u),
+        1 => Vec3::new(-1.0, -v, -u),
+        2 => Vec3::new(u, 1.0, -v),
+        3 => Vec3::new(u, -1.0, v),
+        4 => Vec3::new(-u, -v, 1.0),
+        _ => Vec3::new(u, -v, -1.0),
+    }
+}
+
+/// Skybox image in linear color, laid out the same way as a skybox texture: 6 square layers of
+/// `size` by `size` pixels, one after another.
+pub(crate) struct CubeImage {
+    pub(crate) size: u32,
+    pub(crate) pixels: Vec<Vec4>,
+}
+
+impl CubeImage {
+    /// Creates an image with every pixel set to `fill`.
+    pub(crate) fn new(size: u32, fill: Vec4) -> Self {
+        Self {
+            size,
+            pixels: vec![fill; (size * size) as usize * FACES],
+        }
+    }
+
+    /// Creates an image with each pixel set to `color` of the normalized direction through the
+    /// pixel's center. Since neighboring pixels across an edge between faces are next to each other
+    /// in direction too, images that only depend on the direction have no seams.
+    pub(crate) fn from_fn(size: u32, mut color: impl FnMut(Vec3) -> Vec4) -> Self {
+        let mut pixels = Vec::with_capacity((size * size) as usize * FACES);
+        for face in 0..FACES {
+            for y in 0..size {
+                for x in 0..size {
+                    let uv = (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) / size as f32;
+                    pixels.push(color(face_uv_to_direction(face, uv).normalize()));
+                }
+            }
+        }
+        Self { size, pixels }
+    }
+
+    /// Adds `color` to the image at `direction`, spread over the four nearest pixels so that
+    /// points between pixels don't flicker in and out as the sky rotates.
+    pub(crate) fn splat(&mut self, direction: Vec3, color: Vec4) {
+        let (face, uv) = direction_to_face_uv(direction);
+        let size = self.size as i64;
+        let x = uv.x * self.size as f32 - 0.5;
+        let y = uv.y * self.size as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        for &(dx, dy, weight) in &[
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            let px = (x0 as i64 + dx).clamp(0, size - 1);
+            let py = (y0 as i64 + dy).clamp(0, size - 1);
+            let index = (face as i64 * size * size + py * size + px) as usize;
+            self.pixels[index] += color * weight;
+        }
+    }
+
+    /// Converts the image to a 6-layer sRGB array texture ready to be used as a skybox.
+    pub(crate) fn into_texture(self) -> Texture {
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            let pixel = pixel.clamp(Vec4::ZERO, Vec4::ONE);
+            data.extend_from_slice(&srgb8(Color::rgba_linear(
+                pixel.x, pixel.y, pixel.z, pixel.w,
+            )));
+        }
+        srgb8_texture(self.size, data)
+    }
+}
+
+/// Encodes `color` as the bytes of an `Rgba8UnormSrgb` pixel.
+pub(crate) fn srgb8(color: Color) -> [u8; 4] {
+    let [r, g, b, a] = color.as_rgba_f32();
+    let byte = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [byte(r), byte(g), byte(b), byte(a)]
+}
+
+/// Builds a lookup table from the bytes of an 8-bit color channel to their values from `0.0` to
+/// `1.0`, converted from sRGB to linear color if `srgb` is set.
+pub(crate) fn decode_table(srgb: bool) -> [f32; 256] {
+    let mut table = [0.0; 256];
+    for (value, decoded) in table.iter_mut().enumerate() {
+        let value = value as f32 / 255.0;
+        *decoded = if srgb {
+            Color::rgb(value, 0.0, 0.0).as_linear_rgba_f32()[0]
+        } else {
+            value
+        };
+    }
+    table
+}
+
+/// Creates a 6-layer `Rgba8UnormSrgb` array texture from `data`, with faces `size` pixels wide.
+pub(crate) fn srgb8_texture(size: u32, data: Vec<u8>) -> Texture {
+    Texture::new(
+        Extent3d::new(size, size, FACES as u32),
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_centers_point_along_axes() {
+        let axes = [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z];
+        for (face, axis) in axes.iter().enumerate() {
+            assert_eq!(face_uv_to_direction(face, Vec2::splat(0.5)), *axis);
+            assert_eq!(direction_to_face_uv(*axis), (face, Vec2::splat(0.5)));
+        }
+    }
+
+    #[test]
+    fn pixel_centers_round_trip() {
+        let size = 16;
+        for face in 0..FACES {
+            for y in 0..size {
+                for x in 0..size {
+                    let uv = (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) / size as f32;
+                    let direction = face_uv_to_direction(face, uv);
+                    // The length of the direction shouldn't matter.
+                    for &scale in &[1.0, 1.0 / direction.length(), 3.0] {
+                        let (round_trip_face, round_trip_uv) =
+                            direction_to_face_uv(direction * scale);
+                        assert_eq!(round_trip_face, face, "face of {:?} at {:?}", uv, face);
+                        assert!(
+                            (round_trip_uv - uv).abs().max_element() < 1e-5,
+                            "{:?} on face {} came back as {:?}",
+                            uv,
+                            face,
+                            round_trip_uv,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,700 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optional day/night cycle built on top of the skybox material's texture blending.
+
+use std::f32::consts::TAU;
+
+use bevy::ecs::schedule::SystemLabel;
+use bevy::prelude::*;
+use bevy::render::camera::Camera;
+
+use crate::astronomy::{self, Eclipse, Observer};
+use crate::settings::skyboxes_enabled;
+use crate::transition::TransitionSystem;
+use crate::{SkyboxMaterial, SkyboxSystem, SkyboxTransition};
+
+/// Optional plugin which runs a day/night cycle. Adds the [`TimeOfDay`] resource and systems which
+/// advance it, move entities marked with [`SkyboxSun`] and [`SkyboxMoon`] across the sky, update
+/// the blend factor and color of any skybox with a [`DayNightCycle`] component, draw the sun and
+/// moon in skyboxes with a [`SkyboxSunAndMoon`] component, and track where the sun is on screen for
+/// cameras with a [`SunFlare`] component.
+///
+/// This plugin only drives the cycle; [`SkyboxPlugin`](crate::SkyboxPlugin) must be added too.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{
+/// #     DayNightCycle, SkyboxBundle, SkyboxDayNightPlugin, SkyboxMaterial, SkyboxPlugin,
+/// #     SkyboxSun, SkyboxTextureConversion, TimeOfDay,
+/// # };
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     .add_plugin(SkyboxDayNightPlugin)
+///     // A full day lasts 5 minutes and starts at sunrise.
+///     .insert_resource(TimeOfDay::new(6.0, 300.0))
+///     .add_startup_system(setup.system());
+///
+/// fn setup(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+///     mut skybox_conversion: ResMut<SkyboxTextureConversion>,
+/// ) {
+///     let day: Handle<Texture> = asset_server.load("day_skybox.png");
+///     let night: Handle<Texture> = asset_server.load("night_skybox.png");
+///     skybox_conversion.make_array(day.clone());
+///     skybox_conversion.make_array(night.clone());
+///     commands
+///         .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial {
+///             texture: Some(day),
+///             blend_texture: Some(night),
+///             ..Default::default()
+///         })))
+///         .insert(DayNightCycle::default());
+///     // Use a light as the sun.
+///     commands
+///         .spawn_bundle(LightBundle::default())
+///         .insert(SkyboxSun::default());
+/// }
+/// ```
+pub struct SkyboxDayNightPlugin;
+
+impl Plugin for SkyboxDayNightPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<TimeOfDay>()
+            .add_system(
+                advance_time_of_day
+                    .system()
+                    .label(DayNightSystem::AdvanceTime),
+            )
+            .add_system(
+                move_sun_and_moon
+                    .system()
+                    .label(SkyboxSystem::Animate)
+                    .after(DayNightSystem::AdvanceTime),
+            )
+            .add_system(
+                update_day_night_skyboxes
+                    .system()
+                    .label(SkyboxSystem::Animate)
+                    .with_run_criteria(skyboxes_enabled.system())
+                    .after(DayNightSystem::AdvanceTime)
+                    .after(TransitionSystem::Run),
+            )
+            .add_system(
+                draw_sun_and_moon
+                    .system()
+                    .label(SkyboxSystem::Animate)
+                    .with_run_criteria(skyboxes_enabled.system())
+                    .after(DayNightSystem::AdvanceTime),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, track_sun_flares.system());
+    }
+}
+
+/// Labels for ordering the day/night systems relative to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+enum DayNightSystem {
+    AdvanceTime,
+}
+
+/// Resource tracking the current time of day for the [`SkyboxDayNightPlugin`].
+///
+/// By default, the sun rises in the east (+X) at 6:00, passes directly overhead at noon, and sets
+/// in the west (-X) at 18:00. The moon is always directly opposite the sun. If you want the sun to
+/// follow a different path, rotate the skybox and the parents of the sun and moon entities.
+///
+/// If an [`Observer`] is set, the sun and moon are instead placed where they really are in the sky
+/// at that place on Earth, on the observer's date, at `hour` local time. The observer's date moves
+/// forward whenever the time of day passes midnight. North is -Z.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeOfDay {
+    /// Current time of day in hours, in the range `[0, 24)`. `0.0` is midnight and `12.0` is noon.
+    pub hour: f32,
+    /// Length of a full day in real-time seconds.
+    pub day_length: f32,
+    /// While paused, the time of day doesn't advance on its own, but can still be set manually.
+    pub paused: bool,
+    /// Place and date to compute astronomically accurate sun and moon positions for.
+    pub observer: Option<Observer>,
+}
+
+impl TimeOfDay {
+    /// Creates a running `TimeOfDay` starting at `hour` with a full day lasting `day_length`
+    /// seconds.
+    pub fn new(hour: f32, day_length: f32) -> Self {
+        Self {
+            hour: hour.rem_euclid(24.0),
+            day_length,
+            paused: false,
+            observer: None,
+        }
+    }
+
+    /// Creates a running `TimeOfDay` like [`TimeOfDay::new`], which places the sun and moon
+    /// accurately for `observer`.
+    pub fn with_observer(hour: f32, day_length: f32, observer: Observer) -> Self {
+        Self {
+            observer: Some(observer),
+            ..Self::new(hour, day_length)
+        }
+    }
+
+    /// Direction pointing from the origin towards the sun.
+    pub fn sun_direction(&self) -> Vec3 {
+        match &self.observer {
+            Some(observer) => observer.sun_direction(self.hour as f64),
+            None => {
+                let angle = (self.hour - 6.0) / 24.0 * TAU;
+                Vec3::new(angle.cos(), angle.sin(), 0.0)
+            }
+        }
+    }
+
+    /// Direction pointing from the origin towards the moon.
+    pub fn moon_direction(&self) -> Vec3 {
+        match &self.observer {
+            Some(observer) => observer.moon_direction(self.hour as f64),
+            None => -self.sun_direction(),
+        }
+    }
+
+    /// Apparent angular radius of the sun, in radians.
+    pub fn sun_angular_radius(&self) -> f32 {
+        match &self.observer {
+            Some(observer) => {
+                astronomy::sun_angular_radius(observer.julian_day(self.hour as f64)) as f32
+            }
+            None => MEAN_SUN_ANGULAR_RADIUS,
+        }
+    }
+
+    /// Apparent angular radius of the moon, in radians.
+    pub fn moon_angular_radius(&self) -> f32 {
+        match &self.observer {
+            Some(observer) => {
+                astronomy::moon_angular_radius(observer.julian_day(self.hour as f64)) as f32
+            }
+            None => MEAN_MOON_ANGULAR_RADIUS,
+        }
+    }
+
+    /// How much the moon is covering the sun. Without an [`Observer`] the moon is always opposite
+    /// the sun, so there are never any eclipses.
+    ///
+    /// Use [`Eclipse::sunlight`] to dim the lights in a scene during an eclipse. The
+    /// [`DayNightCycle`] already uses it to darken the sky.
+    pub fn eclipse(&self) -> Eclipse {
+        astronomy::solar_eclipse(
+            self.sun_direction(),
+            self.sun_angular_radius(),
+            self.moon_direction(),
+            self.moon_angular_radius(),
+        )
+    }
+
+    /// How much it is day, from `0.0` (full night) to `1.0` (full day). This fades smoothly while
+    /// the sun is close to the horizon rather than switching abruptly at sunrise and sunset.
+    pub fn daylight(&self) -> f32 {
+        // Fade over the time it takes the sun to move from 6 degrees below to 6 degrees above the
+        // horizon.
+        const FADE: f32 = 0.1;
+        let t = ((self.sun_direction().y + FADE) / (2.0 * FADE)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+/// Average apparent angular radius of the sun, in radians.
+const MEAN_SUN_ANGULAR_RADIUS: f32 = 0.00465;
+/// Average apparent angular radius of the moon, in radians.
+const MEAN_MOON_ANGULAR_RADIUS: f32 = 0.00452;
+
+impl Default for TimeOfDay {
+    /// Starts at noon with a 20 minute day.
+    fn default() -> Self {
+        Self::new(12.0, 20.0 * 60.0)
+    }
+}
+
+/// Marks an entity, usually a light, to be positioned in the direction of the sun by the
+/// [`SkyboxDayNightPlugin`]. The entity is moved to `distance` from the origin and rotated so its
+/// forward (-Z) direction points at the origin.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxSun {
+    /// Distance from the origin to place the sun.
+    pub distance: f32,
+}
+
+impl Default for SkyboxSun {
+    fn default() -> Self {
+        Self { distance: 100.0 }
+    }
+}
+
+/// Marks an entity to be positioned in the direction of the moon by the [`SkyboxDayNightPlugin`].
+/// Works the same way as [`SkyboxSun`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxMoon {
+    /// Distance from the origin to place the moon.
+    pub distance: f32,
+}
+
+impl Default for SkyboxMoon {
+    fn default() -> Self {
+        Self { distance: 100.0 }
+    }
+}
+
+/// Component for skybox entities which should follow the [`TimeOfDay`].
+///
+/// The skybox material's `texture` is used as the day sky and its `blend_texture` as the night sky.
+/// The plugin sets the material's `blend_factor` based on [`TimeOfDay::daylight`], and its `color`
+/// by fading between `night_color` and `day_color`, which can be used to darken the sky at night.
+///
+/// If the material also has a `blend_texture_2` or `blend_texture_3`, those are used as the dawn and
+/// dusk skies respectively. The plugin then sets the material's `blend_weights` to fade from night
+/// to dawn to day to dusk and back to night as the sun crosses the horizon. If only one of them is
+/// set, it's used for both dawn and dusk.
+///
+/// During a solar eclipse, the sky fades towards night as the sun is covered, following
+/// [`Eclipse::sunlight`].
+///
+/// For art-directed sunrises and sunsets, set a `color_ramp`. The material's `color` then comes from
+/// the ramp's zenith colors instead of `day_color` and `night_color`, and its `haze_color` from the
+/// ramp's horizon colors.
+///
+/// Note that this modifies the [`SkyboxMaterial`] asset, so all skyboxes sharing the material will
+/// change with it. While a [`SkyboxTransition`] is fading the skybox, the cycle animates the
+/// material being faded to.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DayNightCycle {
+    /// Color to tint the skybox during the day.
+    pub day_color: Color,
+    /// Color to tint the skybox during the night.
+    pub night_color: Color,
+    /// Zenith and horizon colors keyed by the elevation of the sun.
+    pub color_ramp: Option<SkyColorRamp>,
+}
+
+impl Default for DayNightCycle {
+    fn default() -> Self {
+        Self {
+            day_color: Color::WHITE,
+            night_color: Color::WHITE,
+            color_ramp: None,
+        }
+    }
+}
+
+/// Gradient of sky colors keyed by the sun's elevation, used by [`DayNightCycle`].
+///
+/// Between keys, colors are interpolated linearly. Below the lowest key and above the highest one,
+/// the colors of those keys are held.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyColorRamp;
+/// let ramp = SkyColorRamp::default()
+///     // Deep blue night with a faint glow along the horizon.
+///     .with_key(-12f32.to_radians(), Color::rgb(0.1, 0.1, 0.2), Color::rgba(0.1, 0.1, 0.3, 0.3))
+///     // Golden hour.
+///     .with_key(0.0, Color::rgb(0.9, 0.7, 0.6), Color::rgba(1.0, 0.5, 0.2, 0.8))
+///     // Plain daylight, with a light haze.
+///     .with_key(20f32.to_radians(), Color::WHITE, Color::rgba(0.9, 0.95, 1.0, 0.3));
+/// let (zenith, horizon) = ramp.sample(0.0).unwrap();
+/// assert_eq!(zenith, Color::rgb(0.9, 0.7, 0.6));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyColorRamp {
+    /// Keys of the ramp, sorted by elevation.
+    keys: Vec<SkyColorKey>,
+}
+
+/// Colors of a [`SkyColorRamp`] at a sun elevation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyColorKey {
+    /// Elevation of the sun above the horizon, in radians.
+    pub sun_elevation: f32,
+    /// Color to tint the sky with. Used as the material's `color`.
+    pub zenith: Color,
+    /// Color of the haze near the horizon. Used as the material's `haze_color`, so the alpha sets
+    /// the strength of the haze.
+    pub horizon: Color,
+}
+
+impl SkyColorRamp {
+    /// Adds a key at `sun_elevation` radians, replacing any key already at that elevation.
+    pub fn with_key(mut self, sun_elevation: f32, zenith: Color, horizon: Color) -> Self {
+        self.keys.retain(|key| key.sun_elevation != sun_elevation);
+        let index = self
+            .keys
+            .iter()
+            .position(|key| key.sun_elevation > sun_elevation)
+            .unwrap_or(self.keys.len());
+        self.keys.insert(
+            index,
+            SkyColorKey {
+                sun_elevation,
+                zenith,
+                horizon,
+            },
+        );
+        self
+    }
+
+    /// Keys of the ramp, sorted by elevation.
+    pub fn keys(&self) -> &[SkyColorKey] {
+        &self.keys
+    }
+
+    /// Zenith and horizon colors when the sun is at `sun_elevation` radians, or `None` if the ramp
+    /// has no keys.
+    pub fn sample(&self, sun_elevation: f32) -> Option<(Color, Color)> {
+        let next = self
+            .keys
+            .iter()
+            .position(|key| key.sun_elevation > sun_elevation);
+        let (a, b, t) = match next {
+            None => {
+                let last = self.keys.last()?;
+                (last, last, 0.0)
+            }
+            Some(0) => (&self.keys[0], &self.keys[0], 0.0),
+            Some(next) => {
+                let (a, b) = (&self.keys[next - 1], &self.keys[next]);
+                let t = (sun_elevation - a.sun_elevation) / (b.sun_elevation - a.sun_elevation);
+                (a, b, t)
+            }
+        };
+        if t == 0.0 {
+            return Some((a.zenith, a.horizon));
+        }
+        Some((
+            lerp_color(a.zenith, b.zenith, t),
+            lerp_color(a.horizon, b.horizon, t),
+        ))
+    }
+}
+
+/// Interpolates between two colors in linear space.
+pub(crate) fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let from = Vec4::from(from.as_linear_rgba_f32());
+    let to = Vec4::from(to.as_linear_rgba_f32());
+    let [r, g, b, a] = from.lerp(to, t).into();
+    Color::rgba_linear(r, g, b, a)
+}
+
+/// Component for skybox entities which draws the discs of the sun and moon following the
+/// [`TimeOfDay`], including solar eclipses.
+///
+/// The plugin sets the `sun_*`, `moon_*`, and `corona_color` fields of the skybox's
+/// [`SkyboxMaterial`]. The moon is lit according to its phase and hides the stars behind it at
+/// night. When it covers the sun completely, the sun's corona fades in around it.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::astronomy::Observer;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxSunAndMoon, TimeOfDay};
+/// # fn setup(mut commands: Commands) {
+/// commands.spawn_bundle(SkyboxBundle::default()).insert(SkyboxSunAndMoon::default());
+/// // The total solar eclipse of 2017, as seen from Carbondale, Illinois.
+/// commands.insert_resource(TimeOfDay::with_observer(
+///     13.0,
+///     600.0,
+///     Observer {
+///         latitude: 37.7,
+///         longitude: -89.2,
+///         year: 2017,
+///         month: 8,
+///         day: 21,
+///         utc_offset: -5.0,
+///     },
+/// ));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxSunAndMoon {
+    /// Color of the sun's disc. Brighter-than-white colors work well with bloom.
+    pub sun_color: Color,
+    /// Color of the full moon. The moon gets darker as less of it is lit by the sun.
+    pub moon_color: Color,
+    /// Color of the sun's corona during a total solar eclipse.
+    pub corona_color: Color,
+    /// Factor to scale the sun and moon by. The real sun and moon are only about half a degree
+    /// across, which can be hard to see with a wide field of view.
+    pub scale: f32,
+}
+
+impl Default for SkyboxSunAndMoon {
+    fn default() -> Self {
+        Self {
+            sun_color: Color::rgb(1.0, 0.98, 0.9),
+            moon_color: Color::rgb(0.9, 0.9, 0.85),
+            corona_color: Color::rgb(0.8, 0.85, 1.0),
+            scale: 1.0,
+        }
+    }
+}
+
+/// Component for cameras which tracks where the sun is on screen, for drawing lens flares.
+///
+/// The [`SkyboxDayNightPlugin`] updates this every frame after transforms have been propagated.
+/// Bevy doesn't provide a way to read back the depth buffer, so the plugin can't tell on its own
+/// whether the sun is hidden behind something. To fade flares behind mountains and buildings, set
+/// `occlusion`, for example from a raycast towards the sun or by sampling a few points around it.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SunFlare;
+/// # fn setup(mut commands: Commands) {
+/// commands
+///     .spawn_bundle(PerspectiveCameraBundle::default())
+///     .insert(SunFlare::default());
+/// # }
+///
+/// fn draw_flare(cameras: Query<&SunFlare>) {
+///     for flare in cameras.iter() {
+///         if let Some(position) = flare.screen_position {
+///             // Draw a flare at `position` with an opacity of `flare.visibility`.
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SunFlare {
+    /// Position of the sun in normalized device coordinates, from `(-1, -1)` at the bottom left
+    /// of the screen to `(1, 1)` at the top right. `None` if the sun is behind the camera. The
+    /// position may be slightly off screen while the flare fades out at the edges.
+    pub screen_position: Option<Vec2>,
+    /// How visible the flare should be, from `0.0` to `1.0`. This fades out as the sun sets or
+    /// moves off screen, and is reduced by `occlusion`.
+    pub visibility: f32,
+    /// How much of the sun is hidden behind the scene, from `0.0` (not at all) to `1.0` (fully
+    /// hidden). This is never changed by the plugin.
+    pub occlusion: f32,
+}
+
+/// System which advances the time of day.
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.paused || time_of_day.day_length <= 0.0 {
+        return;
+    }
+    let hour = time_of_day.hour + time.delta_seconds() / time_of_day.day_length * 24.0;
+    let days = (hour / 24.0).floor();
+    time_of_day.hour = hour - days * 24.0;
+    if days != 0.0 {
+        if let Some(observer) = &mut time_of_day.observer {
+            observer.advance_days(days as i64);
+        }
+    }
+}
+
+/// System which positions the sun and moon entities.
+fn move_sun_and_moon(
+    time_of_day: Res<TimeOfDay>,
+    mut suns: Query<(&SkyboxSun, &mut Transform), Without<SkyboxMoon>>,
+    mut moons: Query<(&SkyboxMoon, &mut Transform), Without<SkyboxSun>>,
+) {
+    let sun = time_of_day.sun_direction();
+    for (sun_marker, mut transform) in suns.iter_mut() {
+        *transform = facing_origin(sun, sun_marker.distance);
+    }
+    let moon = time_of_day.moon_direction();
+    for (moon_marker, mut transform) in moons.iter_mut() {
+        *transform = facing_origin(moon, moon_marker.distance);
+    }
+}
+
+/// Transform `distance` from the origin in `direction`, facing the origin.
+fn facing_origin(direction: Vec3, distance: f32) -> Transform {
+    // Without an observer, the sun and moon move in the XY plane, so Z is always perpendicular to
+    // their direction. With one they can be anywhere, so fall back to X when they're near Z.
+    let up = if direction.z.abs() > 0.99 {
+        Vec3::X
+    } else {
+        Vec3::Z
+    };
+    Transform::from_translation(direction * distance).looking_at(Vec3::ZERO, up)
+}
+
+/// System which updates the materials of skyboxes with a [`DayNightCycle`].
+fn update_day_night_skyboxes(
+    time_of_day: Res<TimeOfDay>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    skyboxes: Query<(
+        &DayNightCycle,
+        &Handle<SkyboxMaterial>,
+        Option<&SkyboxTransition>,
+    )>,
+) {
+    let daylight = time_of_day.daylight() * time_of_day.eclipse().sunlight();
+    let sun_elevation = time_of_day.sun_direction().y.clamp(-1.0, 1.0).asin();
+    for (cycle, handle, transition) in skyboxes.iter() {
+        // While a transition is fading the skybox, its handle is to a temporary material which the
+        // transition animates, so animate the material it's fading to instead.
+        let handle = transition.map_or(handle, |transition| &transition.to);
+        let ramp = cycle
+            .color_ramp
+            .as_ref()
+            .and_then(|ramp| ramp.sample(sun_elevation));
+        let (color, haze_color) = match ramp {
+            Some((zenith, horizon)) => (zenith, Some(horizon)),
+            None => (
+                lerp_color(cycle.night_color, cycle.day_color, daylight),
+                None,
+            ),
+        };
+        let blend_factor = 1.0 - daylight;
+        let blend_weights = materials
+            .get(handle)
+            .and_then(|material| twilight_weights(material, &time_of_day, daylight, sun_elevation));
+
+        // Only touch the material if something changed, since getting it mutably causes it to be
+        // re-uploaded.
+        match materials.get(handle) {
+            Some(material)
+                if material.color != color
+                    || material.blend_factor != blend_factor
+                    || material.blend_weights != blend_weights
+                    || matches!(haze_color, Some(haze) if material.haze_color != haze) => {}
+            _ => continue,
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = color;
+            material.blend_factor = blend_factor;
+            material.blend_weights = blend_weights;
+            if let Some(haze_color) = haze_color {
+                material.haze_color = haze_color;
+            }
+        }
+    }
+}
+
+/// System which updates the materials of skyboxes with [`SkyboxSunAndMoon`].
+fn draw_sun_and_moon(
+    time_of_day: Res<TimeOfDay>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    skyboxes: Query<(&SkyboxSunAndMoon, &Handle<SkyboxMaterial>)>,
+) {
+    let sun_direction = time_of_day.sun_direction();
+    let moon_direction = time_of_day.moon_direction();
+    let (sun_radius, moon_radius) = (
+        time_of_day.sun_angular_radius(),
+        time_of_day.moon_angular_radius(),
+    );
+    // Fraction of the moon's face which is lit by the sun.
+    let phase = (1.0 - sun_direction.dot(moon_direction)) / 2.0;
+    let opacity = 1.0 - time_of_day.daylight();
+    let t = ((time_of_day.eclipse().coverage - 0.97) / 0.03).clamp(0.0, 1.0);
+    let totality = t * t * (3.0 - 2.0 * t);
+    for (discs, handle) in skyboxes.iter() {
+        let [r, g, b, _] = discs.moon_color.as_linear_rgba_f32();
+        let moon_color = Color::rgba_linear(r * phase, g * phase, b * phase, opacity);
+        let [r, g, b, _] = discs.corona_color.as_linear_rgba_f32();
+        let corona_color = Color::rgba_linear(r * totality, g * totality, b * totality, 1.0);
+        let (sun_radius, moon_radius) = (sun_radius * discs.scale, moon_radius * discs.scale);
+
+        // Only touch the material if something changed, since getting it mutably causes it to be
+        // re-uploaded.
+        match materials.get(handle) {
+            Some(material)
+                if material.sun_direction != sun_direction
+                    || material.sun_angular_radius != sun_radius
+                    || material.sun_color != discs.sun_color
+                    || material.moon_direction != moon_direction
+                    || material.moon_angular_radius != moon_radius
+                    || material.moon_color != moon_color
+                    || material.corona_color != corona_color => {}
+            _ => continue,
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.sun_direction = sun_direction;
+            material.sun_angular_radius = sun_radius;
+            material.sun_color = discs.sun_color;
+            material.moon_direction = moon_direction;
+            material.moon_angular_radius = moon_radius;
+            material.moon_color = moon_color;
+            material.corona_color = corona_color;
+        }
+    }
+}
+
+/// System which updates [`SunFlare`]s.
+fn track_sun_flares(
+    time_of_day: Res<TimeOfDay>,
+    mut cameras: Query<(&Camera, &GlobalTransform, &mut SunFlare)>,
+) {
+    let sun = time_of_day.sun_direction();
+    // Fade out as the sun touches the horizon.
+    let above_horizon = ((sun.y + 0.02) / 0.07).clamp(0.0, 1.0);
+    for (camera, transform, mut flare) in cameras.iter_mut() {
+        // The sun is infinitely far away, so only the camera's rotation matters.
+        let view = Mat4::from_quat(transform.rotation).inverse();
+        let clip = camera.projection_matrix * view * sun.extend(0.0);
+        if clip.w <= 0.0 {
+            flare.screen_position = None;
+            flare.visibility = 0.0;
+            continue;
+        }
+        let position = clip.truncate().truncate() / clip.w;
+        // Fade out as the sun moves past the edge of the screen.
+        let edge = position.abs().max_element();
+        let on_screen = (1.0 - (edge - 1.0) / 0.2).clamp(0.0, 1.0);
+        flare.screen_position = Some(position);
+        flare.visibility = above_horizon * on_screen * (1.0 - flare.occlusion.clamp(0.0, 1.0));
+    }
+}
+
+/// Blend weights for a day, night, dawn, and dusk sky, or `None` if the material doesn't have dawn
+/// or dusk textures.
+fn twilight_weights(
+    material: &SkyboxMaterial,
+    time_of_day: &TimeOfDay,
+    daylight: f32,
+    sun_elevation: f32,
+) -> Option<Vec4> {
+    // Twilight lasts while the sun is within 12 degrees of the horizon.
+    const TWILIGHT: f32 = 0.21;
+    let (has_dawn, has_dusk) = (
+        material.blend_texture_2.is_some(),
+        material.blend_texture_3.is_some(),
+    );
+    if !has_dawn && !has_dusk {
+        return None;
+    }
+    let t = (1.0 - sun_elevation.abs() / TWILIGHT).clamp(0.0, 1.0);
+    let twilight = t * t * (3.0 - 2.0 * t);
+    let dawn = time_of_day.hour < 12.0;
+    let (dawn_weight, dusk_weight) = match (has_dawn, has_dusk) {
+        (true, false) => (twilight, 0.0),
+        (false, true) => (0.0, twilight),
+        _ if dawn => (twilight, 0.0),
+        _ => (0.0, twilight),
+    };
+    Some(Vec4::new(
+        daylight * (1.0 - twilight),
+        (1.0 - daylight) * (1.0 - twilight),
+        dawn_weight,
+        dusk_weight,
+    ))
+}
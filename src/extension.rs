@@ -0,0 +1,165 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Custom materials layered on top of the skybox shader.
+
+use std::marker::PhantomData;
+
+use bevy::asset::Asset;
+use bevy::prelude::*;
+use bevy::render::pipeline::PipelineDescriptor;
+use bevy::render::render_graph::{base, AssetRenderResourcesNode, RenderGraph};
+use bevy::render::renderer::RenderResources;
+use bevy::render::shader::ShaderStage;
+
+use crate::add_skybox_pipeline_variant;
+
+/// Extra uniforms, textures, and shader code added on top of a
+/// [`SkyboxMaterial`](crate::SkyboxMaterial), for custom sky effects such as scrolling auroras or
+/// sci-fi grids.
+///
+/// The extension's `FRAGMENT_SOURCE` is inserted into the skybox fragment shader, so everything the
+/// skybox already does, including keeping it behind the rest of the scene, still applies. The
+/// source must define `vec4 skyboxExtension(vec4 color, vec3 direction)`, which is called at the
+/// end of the shader with the finished skybox color and the normalized world-space direction of
+/// the pixel, and returns the final color. The extension's render resources are bound to set 3,
+/// with the same names that Bevy uses for any other material, and can be declared in the source.
+///
+/// Add a [`SkyboxExtensionPlugin`] for each extension, then draw skyboxes with the extension's
+/// pipeline from [`SkyboxExtensionPipeline`] and add a handle to the extension to them alongside
+/// their `SkyboxMaterial`.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::reflect::TypeUuid;
+/// # use bevy::render::renderer::RenderResources;
+/// # use bevy_skybox_cubemap::{
+/// #     SkyboxBundle, SkyboxExtensionPipeline, SkyboxExtensionPlugin, SkyboxMaterial,
+/// #     SkyboxMaterialExtension, SkyboxPlugin,
+/// # };
+/// #[derive(RenderResources, TypeUuid)]
+/// #[uuid = "5f7e9b0c-3a4d-4e1f-9c2b-8d6a1e0f4b73"]
+/// struct Aurora {
+///     tint: Color,
+///     time: f32,
+/// }
+///
+/// impl SkyboxMaterialExtension for Aurora {
+///     const FRAGMENT_SOURCE: &'static str = r#"
+///         layout(set = 3, binding = 0) uniform Aurora_tint {
+///             vec4 auroraTint;
+///         };
+///         layout(set = 3, binding = 1) uniform Aurora_time {
+///             float auroraTime;
+///         };
+///
+///         vec4 skyboxExtension(vec4 color, vec3 direction) {
+///             float wave = sin(direction.x * 8.0 + auroraTime) * 0.5 + 0.5;
+///             float band = smoothstep(0.2, 0.5, direction.y)
+///                 * (1.0 - smoothstep(0.5, 0.9, direction.y));
+///             return color + auroraTint * wave * band;
+///         }
+///     "#;
+/// }
+///
+/// fn setup(
+///     mut commands: Commands,
+///     pipeline: Res<SkyboxExtensionPipeline<Aurora>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+///     mut auroras: ResMut<Assets<Aurora>>,
+/// ) {
+///     commands
+///         .spawn_bundle(
+///             SkyboxBundle::new(skyboxes.add(SkyboxMaterial::from_color(Color::BLACK)))
+///                 .with_pipeline(pipeline.handle.clone()),
+///         )
+///         .insert(auroras.add(Aurora {
+///             tint: Color::rgb(0.1, 0.8, 0.4),
+///             time: 0.0,
+///         }));
+/// }
+///
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     .add_plugin(SkyboxExtensionPlugin::<Aurora>::default())
+///     .add_startup_system(setup.system());
+/// ```
+pub trait SkyboxMaterialExtension: RenderResources + Asset {
+    /// GLSL source inserted into the skybox fragment shader. See the trait documentation for what
+    /// it must contain.
+    const FRAGMENT_SOURCE: &'static str;
+}
+
+/// Plugin which sets up a [`SkyboxMaterialExtension`]. Adds `E` as an asset, binds its render
+/// resources for skyboxes which have a `Handle<E>`, and adds a [`SkyboxExtensionPipeline<E>`]
+/// resource with the pipeline to draw them with.
+///
+/// Must be added after [`SkyboxPlugin`](crate::SkyboxPlugin).
+pub struct SkyboxExtensionPlugin<E>(PhantomData<E>);
+
+impl<E> Default for SkyboxExtensionPlugin<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: SkyboxMaterialExtension> Plugin for SkyboxExtensionPlugin<E> {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<E>();
+        let world = app.world_mut();
+        let node = format!("skybox_extension_{}", std::any::type_name::<E>());
+        {
+            let mut graph = world.get_resource_mut::<RenderGraph>().unwrap();
+            graph.add_system_node(node.clone(), AssetRenderResourcesNode::<E>::new(true));
+            graph.add_node_edge(node, base::node::MAIN_PASS).unwrap();
+        }
+
+        let fragment = world
+            .get_resource_mut::<Assets<Shader>>()
+            .unwrap()
+            .add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                &extended_fragment_source(E::FRAGMENT_SOURCE),
+            ));
+        let mut pipelines = world
+            .get_resource_mut::<Assets<PipelineDescriptor>>()
+            .unwrap();
+        let handle = add_skybox_pipeline_variant(&mut pipelines, |pipeline| {
+            pipeline.shader_stages.fragment = Some(fragment);
+        });
+        world.insert_resource(SkyboxExtensionPipeline::<E> {
+            handle,
+            marker: PhantomData,
+        });
+    }
+}
+
+/// Resource holding the pipeline for drawing skyboxes with the [`SkyboxMaterialExtension`] `E`.
+/// Added by [`SkyboxExtensionPlugin<E>`]; use it with
+/// [`SkyboxBundle::with_pipeline`](crate::SkyboxBundle::with_pipeline).
+pub struct SkyboxExtensionPipeline<E> {
+    /// Handle to the pipeline.
+    pub handle: Handle<PipelineDescriptor>,
+    marker: PhantomData<fn() -> E>,
+}
+
+/// Builds the skybox fragment shader with `extension` inserted.
+fn extended_fragment_source(extension: &str) -> String {
+    include_str!("skybox.frag").replacen(
+        "// SKYBOX_EXTENSION_SOURCE",
+        &format!("#define SKYBOX_EXTENSION\n{}", extension),
+        1,
+    )
+}
@@ -0,0 +1,392 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Editing skybox textures on the CPU, from picking out single faces to compositing whole skies.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::cube::{decode_table, direction_to_face_uv, face_uv_to_direction, srgb8, FACES};
+use crate::SkyboxBlendMode;
+
+/// One of the six faces of a skybox. See the crate docs for how each face is oriented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CubeFace {
+    /// The +X face.
+    Right,
+    /// The -X face.
+    Left,
+    /// The +Y face.
+    Top,
+    /// The -Y face.
+    Bottom,
+    /// The +Z face.
+    Back,
+    /// The -Z face.
+    Front,
+}
+
+impl CubeFace {
+    /// Every face, in the same order as the layers of a skybox texture.
+    pub const ALL: [CubeFace; FACES] = [
+        CubeFace::Right,
+        CubeFace::Left,
+        CubeFace::Top,
+        CubeFace::Bottom,
+        CubeFace::Back,
+        CubeFace::Front,
+    ];
+
+    /// Layer of a skybox texture holding this face, and the face number used by
+    /// [`SkyboxTextureBuilder`](crate::SkyboxTextureBuilder).
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Copies a single face out of a skybox texture into a square 2D texture of its own, for example
+/// to show a thumbnail of a sky in an editor or to save the faces as separate images. The face has
+/// the same format as the skybox, and is upright as in the unconverted `N` by `6*N` image.
+///
+/// The skybox texture can be either a 6-layer array texture or a stacked `N` by `6*N` image which
+/// hasn't been converted yet, in any uncompressed format. Only the first sky of a texture holding
+/// several is used. Returns `None` if the texture is too small to hold six faces.
+///
+/// ```
+/// # use bevy_skybox_cubemap::{extract_face, generate_test_pattern_cubemap, CubeFace};
+/// let skybox = generate_test_pattern_cubemap(64);
+/// let front = extract_face(&skybox, CubeFace::Front).unwrap();
+/// assert_eq!((front.size.width, front.size.height), (64, 64));
+/// ```
+pub fn extract_face(skybox: &Texture, face: CubeFace) -> Option<Texture> {
+    let size = skybox.size.width;
+    let face_len = (size * size) as usize * skybox.format.pixel_size();
+    if size == 0 || skybox.data.len() < face_len * FACES {
+        return None;
+    }
+    let start = face.index() * face_len;
+    Some(Texture::new(
+        Extent3d::new(size, size, 1),
+        TextureDimension::D2,
+        skybox.data[start..start + face_len].to_vec(),
+        skybox.format,
+    ))
+}
+
+/// Turns a whole skybox texture around the vertical axis by `quarter_turns` right angles, such as
+/// to fix a sky authored with its front on the wrong face. The sky looks as it did with a rotation
+/// of `Quat::from_rotation_y(quarter_turns as f32 * FRAC_PI_2)` on the skybox's `Transform`: the
+/// top and bottom faces are turned in place, and the side faces swap around, so positive turns move
+/// what was in front to the left. Negative turns go the other way.
+///
+/// The skybox texture can be either an array texture or a stacked image which hasn't been
+/// converted yet, in any uncompressed format, and the result is laid out the same way. Every sky
+/// of a texture holding several is turned. Returns `None` if the texture doesn't hold whole skies.
+///
+/// ```
+/// # use bevy_skybox_cubemap::{generate_test_pattern_cubemap, rotate_skybox_y};
+/// let skybox = generate_test_pattern_cubemap(64);
+/// let turned = rotate_skybox_y(&skybox, 1).unwrap();
+/// assert_eq!(rotate_skybox_y(&turned, -1).unwrap().data, skybox.data);
+/// assert_eq!(rotate_skybox_y(&skybox, 4).unwrap().data, skybox.data);
+/// ```
+pub fn rotate_skybox_y(skybox: &Texture, quarter_turns: i32) -> Option<Texture> {
+    let size = skybox.size.width;
+    let pixel_size = skybox.format.pixel_size();
+    let face_len = (size * size) as usize * pixel_size;
+    let sky_len = face_len * FACES;
+    if size == 0 || skybox.data.is_empty() {
+        return None;
+    }
+    let skies = skybox.data.chunks_exact(sky_len);
+    if !skies.remainder().is_empty() {
+        return None;
+    }
+
+    let turns = quarter_turns.rem_euclid(4);
+    let mut data = Vec::with_capacity(skybox.data.len());
+    for sky in skies {
+        for face in 0..FACES {
+            for y in 0..size {
+                for x in 0..size {
+                    let uv = (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) / size as f32;
+                    // Each pixel shows what the unturned sky showed in the direction turned back.
+                    let mut direction = face_uv_to_direction(face, uv);
+                    for _ in 0..turns {
+                        direction = Vec3::new(-direction.z, direction.y, direction.x);
+                    }
+                    let (from_face, from_uv) = direction_to_face_uv(direction);
+                    // Quarter turns line pixels up exactly, so the nearest pixel is the right one.
+                    let from = (from_uv * size as f32)
+                        .floor()
+                        .clamp(Vec2::ZERO, Vec2::splat(size as f32 - 1.0));
+                    let index = from_face * face_len
+                        + (from.y as usize * size as usize + from.x as usize) * pixel_size;
+                    data.extend_from_slice(&sky[index..index + pixel_size]);
+                }
+            }
+        }
+    }
+    Some(Texture::new(
+        skybox.size,
+        skybox.dimension,
+        data,
+        skybox.format,
+    ))
+}
+
+/// Filter used by [`resize_skybox`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResizeFilter {
+    /// Averages every source pixel each new pixel covers. Fast, and free of ringing, but soft.
+    Box,
+    /// Three-lobed Lanczos filter, which keeps details sharper at the cost of slight ringing
+    /// around hard edges such as the sun.
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    /// How far the filter reaches from the center of a pixel, in pixels.
+    fn radius(self) -> f32 {
+        match self {
+            ResizeFilter::Box => 0.5,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// Weight of a pixel `t` pixels from the center.
+    fn weight(self, t: f32) -> f32 {
+        match self {
+            ResizeFilter::Box => {
+                if t.abs() <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Lanczos3 => {
+                let sinc = |t: f32| {
+                    if t == 0.0 {
+                        1.0
+                    } else {
+                        (PI * t).sin() / (PI * t)
+                    }
+                };
+                if t.abs() < 3.0 {
+                    sinc(t) * sinc(t / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Resamples a skybox texture to faces `size` pixels wide, such as to make a small copy of a
+/// detailed sky for lower graphics settings or reflection probes. Each face is resized on its own
+/// using `filter`, and pixels are blended in their stored encoding, like most image resizing does.
+///
+/// The skybox texture can be either an array texture or a stacked image which hasn't been
+/// converted yet, and must be in `Rgba8UnormSrgb` or `Rgba8Unorm` format; the result is laid out
+/// the same way, in the same format. Every sky of a texture holding several is resized. Returns
+/// `None` if the texture isn't in a supported format or doesn't hold whole skies.
+///
+/// ```
+/// # use bevy_skybox_cubemap::{generate_test_pattern_cubemap, resize_skybox, ResizeFilter};
+/// let skybox = generate_test_pattern_cubemap(256);
+/// let probe = resize_skybox(&skybox, 32, ResizeFilter::Box).unwrap();
+/// assert_eq!((probe.size.width, probe.size.height, probe.size.depth), (32, 32, 6));
+/// ```
+pub fn resize_skybox(skybox: &Texture, size: u32, filter: ResizeFilter) -> Option<Texture> {
+    if !matches!(
+        skybox.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+    let from_size = skybox.size.width as usize;
+    let face_len = from_size * from_size * 4;
+    if from_size == 0 || size == 0 || skybox.data.is_empty() {
+        return None;
+    }
+    if !skybox
+        .data
+        .chunks_exact(face_len * FACES)
+        .remainder()
+        .is_empty()
+    {
+        return None;
+    }
+    let faces = skybox.data.chunks_exact(face_len);
+
+    // Both axes of every face are resampled the same way, so work out the weights once.
+    let size = size as usize;
+    let scale = from_size as f32 / size as f32;
+    let spread = scale.max(1.0);
+    let taps: Vec<(usize, Vec<f32>)> = (0..size)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let reach = filter.radius() * spread;
+            let start = ((center - reach).floor().max(0.0) as usize).min(from_size - 1);
+            let end = ((center + reach).ceil() as usize).clamp(start + 1, from_size);
+            let mut weights: Vec<f32> = (start..end)
+                .map(|j| filter.weight((j as f32 + 0.5 - center) / spread))
+                .collect();
+            let total: f32 = weights.iter().sum();
+            if total.abs() < f32::EPSILON {
+                // Upscaling with the box filter can fall between pixels; use the nearest one.
+                let nearest = (center as usize).clamp(start, end - 1);
+                weights = (start..end).map(|j| (j == nearest) as u8 as f32).collect();
+            } else {
+                weights.iter_mut().for_each(|weight| *weight /= total);
+            }
+            (start, weights)
+        })
+        .collect();
+
+    let layers = faces.len();
+    let mut data = Vec::with_capacity(size * size * 4 * layers);
+    let mut rows = vec![Vec4::ZERO; from_size * size];
+    for face in faces {
+        let pixel = |x: usize, y: usize| {
+            let i = (y * from_size + x) * 4;
+            Vec4::new(
+                face[i] as f32,
+                face[i + 1] as f32,
+                face[i + 2] as f32,
+                face[i + 3] as f32,
+            )
+        };
+        // Resample each row across, then the columns of the result down.
+        for y in 0..from_size {
+            for (x, (start, weights)) in taps.iter().enumerate() {
+                rows[y * size + x] = weights
+                    .iter()
+                    .enumerate()
+                    .fold(Vec4::ZERO, |total, (j, weight)| {
+                        total + pixel(start + j, y) * *weight
+                    });
+            }
+        }
+        for (start, weights) in &taps {
+            for x in 0..size {
+                let color: Vec4 = weights
+                    .iter()
+                    .enumerate()
+                    .fold(Vec4::ZERO, |total, (j, weight)| {
+                        total + rows[(start + j) * size + x] * *weight
+                    });
+                let color = color.clamp(Vec4::ZERO, Vec4::splat(255.0)).round();
+                data.extend_from_slice(&[
+                    color.x as u8,
+                    color.y as u8,
+                    color.z as u8,
+                    color.w as u8,
+                ]);
+            }
+        }
+    }
+    let (width, height, depth) = if skybox.size.depth == 1 {
+        (size, size * layers, 1)
+    } else {
+        (size, size, layers)
+    };
+    Some(Texture::new(
+        Extent3d::new(width as u32, height as u32, depth as u32),
+        skybox.dimension,
+        data,
+        skybox.format,
+    ))
+}
+
+/// Composites `overlay` over `base`, as if the overlay were drawn as a second skybox in front of
+/// the base with [`skybox_pipeline_with_blend_mode`](crate::skybox_pipeline_with_blend_mode) and
+/// `mode`, such as to bake stars over a gradient once while loading instead of layering two
+/// skyboxes every frame. Colors are blended in linear space like the GPU does, and alphas are
+/// combined the same way as colors, except that additive overlays leave the base's alpha alone.
+///
+/// Both textures must have faces of the same size, hold the same number of skies, and be in
+/// `Rgba8UnormSrgb` or `Rgba8Unorm` format. The result is laid out like `base` and in the same
+/// format. Returns `None` if the textures don't match.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::*;
+/// let gradient = generate_gradient_cubemap(Color::NAVY, Color::MIDNIGHT_BLUE, Color::BLACK, 64);
+/// let stars = StarCubemap {
+///     size: 64,
+///     ..Default::default()
+/// }
+/// .bake(&RandomStars::default().generate());
+/// let sky = composite_cubemaps(&gradient, &stars, SkyboxBlendMode::Additive).unwrap();
+/// ```
+pub fn composite_cubemaps(
+    base: &Texture,
+    overlay: &Texture,
+    mode: SkyboxBlendMode,
+) -> Option<Texture> {
+    let is_srgb = |texture: &Texture| match texture.format {
+        TextureFormat::Rgba8UnormSrgb => Some(true),
+        TextureFormat::Rgba8Unorm => Some(false),
+        _ => None,
+    };
+    let (base_srgb, overlay_srgb) = (is_srgb(base)?, is_srgb(overlay)?);
+    if base.size.width != overlay.size.width
+        || base.data.len() != overlay.data.len()
+        || base.data.is_empty()
+    {
+        return None;
+    }
+
+    let (base_decode, overlay_decode) = (decode_table(base_srgb), decode_table(overlay_srgb));
+    let decode = |pixel: &[u8], table: &[f32; 256]| {
+        Vec4::new(
+            table[pixel[0] as usize],
+            table[pixel[1] as usize],
+            table[pixel[2] as usize],
+            pixel[3] as f32 / 255.0,
+        )
+    };
+    let mut data = Vec::with_capacity(base.data.len());
+    for (below, above) in base.data.chunks_exact(4).zip(overlay.data.chunks_exact(4)) {
+        let (below, above) = (decode(below, &base_decode), decode(above, &overlay_decode));
+        let blended = match mode {
+            SkyboxBlendMode::Opaque => above,
+            SkyboxBlendMode::Alpha => above * above.w + below * (1.0 - above.w),
+            SkyboxBlendMode::Additive => {
+                (above.truncate() * above.w + below.truncate()).extend(below.w)
+            }
+            SkyboxBlendMode::Premultiplied => above + below * (1.0 - above.w),
+        }
+        .clamp(Vec4::ZERO, Vec4::ONE);
+        if base_srgb {
+            data.extend_from_slice(&srgb8(Color::rgba_linear(
+                blended.x, blended.y, blended.z, blended.w,
+            )));
+        } else {
+            let blended = (blended * 255.0).round();
+            data.extend_from_slice(&[
+                blended.x as u8,
+                blended.y as u8,
+                blended.z as u8,
+                blended.w as u8,
+            ]);
+        }
+    }
+    Some(Texture::new(base.size, base.dimension, data, base.format))
+}
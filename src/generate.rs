@@ -0,0 +1,630 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Skybox textures built and generated on the CPU.
+
+use bevy::prelude::*;
+
+use crate::cube::{face_uv_to_direction, srgb8, srgb8_texture, CubeImage, FACES};
+use crate::random::Rng;
+
+/// Builder for skybox textures from raw pixels or functions of each pixel's position, which takes
+/// care of the texture's layout and format.
+///
+/// Faces are numbered in the same order as the texture's layers: right (+X), left (-X), top (+Y),
+/// bottom (-Y), back (+Z), and front (-Z); see the crate docs for how each face is oriented.
+/// Texture coordinates on each face are from `0.0` to `1.0`, starting in the top left corner.
+/// Pixels start out black, and the built texture is a 6-layer `Rgba8UnormSrgb` array texture.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyboxTextureBuilder;
+/// # fn setup(mut textures: ResMut<Assets<Texture>>, front_pixels: Vec<u8>) {
+/// let texture = SkyboxTextureBuilder::new(256)
+///     // Fade from blue at the top of each side face to white at the bottom.
+///     .with_fn(|_face, uv| Color::rgb(uv.y, uv.y, 1.0))
+///     .with_face_fn(2, |_uv| Color::rgb(0.0, 0.0, 1.0))
+///     .with_face_fn(3, |_uv| Color::WHITE)
+///     // Replace the front face with an image.
+///     .with_face_data(5, &front_pixels)
+///     .build();
+/// let texture = textures.add(texture);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkyboxTextureBuilder {
+    size: u32,
+    data: Vec<u8>,
+}
+
+impl SkyboxTextureBuilder {
+    /// Creates a builder for a texture with faces `size` pixels wide.
+    pub fn new(size: u32) -> Self {
+        let mut data = Vec::with_capacity((size * size) as usize * FACES * 4);
+        for _ in 0..(size * size) as usize * FACES {
+            data.extend_from_slice(&[0, 0, 0, 255]);
+        }
+        Self { size, data }
+    }
+
+    /// Width and height of each face, in pixels.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Copies `data` into `face`. The data must be `size * size` pixels in `Rgba8UnormSrgb`
+    /// format, row by row from the top, like the pixels of a PNG image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `face` isn't from `0` to `5` or `data` is the wrong length.
+    pub fn with_face_data(mut self, face: usize, data: &[u8]) -> Self {
+        let range = self.face_range(face);
+        assert_eq!(
+            data.len(),
+            range.len(),
+            "face data must be {} by {} RGBA pixels",
+            self.size,
+            self.size
+        );
+        self.data[range].copy_from_slice(data);
+        self
+    }
+
+    /// Sets each pixel of `face` to `color` of the texture coordinates of its center.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `face` isn't from `0` to `5`.
+    pub fn with_face_fn(mut self, face: usize, mut color: impl FnMut(Vec2) -> Color) -> Self {
+        let range = self.face_range(face);
+        let size = self.size;
+        for (i, pixel) in self.data[range].chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i as u32 % size, i as u32 / size);
+            let uv = (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) / size as f32;
+            pixel.copy_from_slice(&srgb8(color(uv)));
+        }
+        self
+    }
+
+    /// Sets every pixel to `color` of its face and the texture coordinates of its center.
+    pub fn with_fn(mut self, mut color: impl FnMut(usize, Vec2) -> Color) -> Self {
+        for face in 0..FACES {
+            self = self.with_face_fn(face, |uv| color(face, uv));
+        }
+        self
+    }
+
+    /// Sets every pixel to `color` of the normalized direction the skybox shows it in, before the
+    /// skybox's rotation. Functions of the direction have no seams between faces.
+    pub fn with_direction_fn(self, mut color: impl FnMut(Vec3) -> Color) -> Self {
+        self.with_fn(|face, uv| color(face_uv_to_direction(face, uv).normalize()))
+    }
+
+    /// Builds the texture.
+    pub fn build(self) -> Texture {
+        srgb8_texture(self.size, self.data)
+    }
+
+    /// Range of `data` holding `face`.
+    fn face_range(&self, face: usize) -> std::ops::Range<usize> {
+        assert!(face < FACES, "skybox textures only have {} faces", FACES);
+        let len = (self.size * self.size) as usize * 4;
+        face * len..(face + 1) * len
+    }
+}
+
+/// Bakes a test pattern into a skybox texture with faces `size` pixels wide, for checking how a
+/// skybox is oriented without any assets. Each face has its own color, a grid, its name and axis
+/// (such as "+X RIGHT") written across the middle, and an arrow pointing to the top of the face as
+/// laid out in the crate docs. The labels read correctly from inside the skybox.
+///
+/// Returns a 6-layer array texture. Faces should be at least 64 pixels wide for the labels to be
+/// readable.
+pub fn generate_test_pattern_cubemap(size: u32) -> Texture {
+    const FACE_COLORS: [[f32; 3]; FACES] = [
+        [0.8, 0.25, 0.25],
+        [0.25, 0.8, 0.8],
+        [0.25, 0.8, 0.25],
+        [0.8, 0.25, 0.8],
+        [0.25, 0.25, 0.8],
+        [0.8, 0.8, 0.25],
+    ];
+    const LABELS: [(&str, &str); FACES] = [
+        ("+X", "RIGHT"),
+        ("-X", "LEFT"),
+        ("+Y", "TOP"),
+        ("-Y", "BOTTOM"),
+        ("+Z", "BACK"),
+        ("-Z", "FRONT"),
+    ];
+    // Glyphs are scaled up to whole pixels, so the labels stay crisp. The longest name, "BOTTOM",
+    // is 35 glyph pixels wide, and should fit in three quarters of the face.
+    let name_scale = (size * 3 / 4 / 35).max(1);
+    let axis_scale = name_scale * 3 / 2;
+    let size_f = size as f32;
+    SkyboxTextureBuilder::new(size)
+        .with_fn(|face, uv| {
+            let (x, y) = ((uv.x * size_f) as i32, (uv.y * size_f) as i32);
+            let (axis, name) = LABELS[face];
+            let axis_top = size as i32 / 2 - (GLYPH_HEIGHT * axis_scale) as i32 - 2;
+            let name_top = size as i32 / 2 + 2;
+            let text = draws_text(axis, axis_scale, axis_top, size, x, y)
+                || draws_text(name, name_scale, name_top, size, x, y);
+            let arrow = draws_up_arrow(uv);
+            // Thin grid lines every eighth of the face, with thicker lines around its edges.
+            let cell = uv * 8.0;
+            let line_width = 8.0 / size_f;
+            let grid = (cell.x - cell.x.round()).abs() < line_width
+                || (cell.y - cell.y.round()).abs() < line_width;
+            let edge = uv.min_element() < 3.0 / size_f || uv.max_element() > 1.0 - 3.0 / size_f;
+            let [r, g, b] = FACE_COLORS[face];
+            if text || arrow || edge {
+                Color::WHITE
+            } else if grid {
+                Color::rgb(r * 0.6, g * 0.6, b * 0.6)
+            } else {
+                Color::rgb(r, g, b)
+            }
+        })
+        .build()
+}
+
+/// Width of each glyph in [`GLYPHS`], in glyph pixels.
+const GLYPH_WIDTH: u32 = 5;
+/// Height of each glyph in [`GLYPHS`], in glyph pixels.
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Tiny bitmap font with just the characters needed for the test pattern labels. Each row is a
+/// byte with the leftmost pixel in bit 4.
+const GLYPHS: [(char, [u8; GLYPH_HEIGHT as usize]); 21] = [
+    ('+', [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    ('A', [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('B', [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e]),
+    ('C', [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e]),
+    ('E', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f]),
+    ('F', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10]),
+    ('G', [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f]),
+    ('H', [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('I', [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f]),
+    ('M', [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x11]),
+    ('O', [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('P', [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10]),
+    ('R', [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11]),
+    ('T', [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('X', [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x0a, 0x04, 0x04, 0x04, 0x04]),
+    ('Z', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f]),
+];
+
+/// Whether pixel `x`, `y` of a face `size` pixels wide is covered by `text`, drawn horizontally
+/// centered with its top at `top` and each glyph pixel `scale` pixels wide.
+fn draws_text(text: &str, scale: u32, top: i32, size: u32, x: i32, y: i32) -> bool {
+    // Each glyph is followed by a column of spacing.
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    let width = (text.chars().count() as u32 * advance - scale) as i32;
+    let left = (size as i32 - width) / 2;
+    let (dx, dy) = (x - left, y - top);
+    if dx < 0 || dy < 0 || dx >= width || dy >= (GLYPH_HEIGHT * scale) as i32 {
+        return false;
+    }
+    let (dx, dy) = (dx as u32, dy as u32);
+    let column = dx % advance / scale;
+    let glyph = match text.chars().nth((dx / advance) as usize) {
+        Some(c) => GLYPHS.iter().find(|(glyph, _)| *glyph == c),
+        None => None,
+    };
+    match glyph {
+        Some((_, rows)) if column < GLYPH_WIDTH => {
+            rows[(dy / scale) as usize] & (0x10 >> column) != 0
+        }
+        _ => false,
+    }
+}
+
+/// Whether `uv` is covered by an arrow in the top quarter of a face, pointing to its top edge.
+fn draws_up_arrow(uv: Vec2) -> bool {
+    let x = (uv.x - 0.5).abs();
+    let head = (0.08..0.16).contains(&uv.y) && x < (uv.y - 0.08) * 0.75;
+    let stem = (0.16..0.26).contains(&uv.y) && x < 0.02;
+    head || stem
+}
+
+/// Bakes a sky which fades from `horizon` at the horizon up to `zenith` straight up, and down to
+/// `ground` straight down, into a skybox texture with faces `size` pixels wide. The colors are
+/// blended in linear color, evenly by angle from the horizon.
+///
+/// Returns a 6-layer array texture. For a gradient which doesn't need to be a texture, a plain
+/// color [`SkyboxMaterial`](crate::SkyboxMaterial) with its `haze_*` and
+/// `ground_*` fields set is cheaper.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{generate_gradient_cubemap, SkyboxBundle, SkyboxMaterial};
+/// fn setup(
+///     mut commands: Commands,
+///     mut textures: ResMut<Assets<Texture>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     let texture = textures.add(generate_gradient_cubemap(
+///         Color::rgb(0.1, 0.3, 0.8),
+///         Color::rgb(0.7, 0.8, 0.9),
+///         Color::rgb(0.3, 0.25, 0.2),
+///         256,
+///     ));
+///     commands.spawn_bundle(SkyboxBundle::new(
+///         skyboxes.add(SkyboxMaterial::from_texture(texture)),
+///     ));
+/// }
+/// ```
+pub fn generate_gradient_cubemap(
+    zenith: Color,
+    horizon: Color,
+    ground: Color,
+    size: u32,
+) -> Texture {
+    let zenith = Vec4::from(zenith.as_linear_rgba_f32());
+    let horizon = Vec4::from(horizon.as_linear_rgba_f32());
+    let ground = Vec4::from(ground.as_linear_rgba_f32());
+    CubeImage::from_fn(size, |direction| {
+        let elevation = direction.y.clamp(-1.0, 1.0).asin() / std::f32::consts::FRAC_PI_2;
+        if elevation >= 0.0 {
+            horizon.lerp(zenith, elevation)
+        } else {
+            horizon.lerp(ground, -elevation)
+        }
+    })
+    .into_texture()
+}
+
+/// Settings for baking a nebula or cloudscape into a skybox texture from fractal noise, for space
+/// skies.
+///
+/// The noise is sampled in 3D along the direction through each pixel, so it wraps around the faces
+/// without seams or stretching. Noise values from `0.0` to `1.0` are turned into colors with
+/// `colors`. The same settings always bake the same sky, and different `seed`s give different
+/// skies. [`bake`](Self::bake) returns a 6-layer array texture.
+///
+/// Baking takes a while for big textures, especially in debug builds, so it's best done once while
+/// loading.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{NebulaCubemap, SkyboxBundle, SkyboxMaterial};
+/// fn setup(
+///     mut commands: Commands,
+///     mut textures: ResMut<Assets<Texture>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     let nebula = NebulaCubemap {
+///         seed: 42,
+///         colors: vec![
+///             (0.45, Color::BLACK),
+///             (0.6, Color::rgb(0.05, 0.1, 0.3)),
+///             (0.8, Color::rgb(0.6, 0.3, 0.8)),
+///         ],
+///         ..Default::default()
+///     };
+///     let texture = textures.add(nebula.bake());
+///     commands.spawn_bundle(SkyboxBundle::new(
+///         skyboxes.add(SkyboxMaterial::from_texture(texture)),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NebulaCubemap {
+    /// Width and height of each face of the texture, in pixels.
+    pub size: u32,
+    /// Seed for the noise.
+    pub seed: u64,
+    /// Number of times the largest features of the noise repeat around the sky, roughly.
+    pub frequency: f32,
+    /// Number of layers of noise, each with finer detail than the last.
+    pub octaves: u32,
+    /// How much finer each layer's detail is than the last.
+    pub lacunarity: f32,
+    /// How much each layer's strength is scaled by compared to the last.
+    pub gain: f32,
+    /// Use ridged noise, which has sharp bright ridges like filaments or veins, instead of the
+    /// soft billows of plain fractal noise.
+    pub ridged: bool,
+    /// Color ramp for the noise: pairs of noise values and the colors at those values, in
+    /// increasing order. Noise values between them are blended in linear color, and values outside
+    /// of them take the color of the nearest end. Plain fractal noise is mostly between `0.3` and
+    /// `0.7`, and ridged noise between `0.2` and `0.9`. The alpha is kept, so a nebula can be
+    /// blended over another sky.
+    pub colors: Vec<(f32, Color)>,
+}
+
+impl NebulaCubemap {
+    /// Bakes the nebula into a skybox texture.
+    pub fn bake(&self) -> Texture {
+        let colors: Vec<_> = self
+            .colors
+            .iter()
+            .map(|&(value, color)| (value, Vec4::from(color.as_linear_rgba_f32())))
+            .collect();
+        let noise = GradientNoise::new(self.seed);
+        CubeImage::from_fn(self.size, |direction| {
+            let value = noise.fractal(
+                direction * self.frequency,
+                self.octaves,
+                self.lacunarity,
+                self.gain,
+                self.ridged,
+            );
+            sample_ramp(&colors, value)
+        })
+        .into_texture()
+    }
+}
+
+impl Default for NebulaCubemap {
+    fn default() -> Self {
+        Self {
+            size: 512,
+            seed: 0,
+            frequency: 2.0,
+            octaves: 6,
+            lacunarity: 2.0,
+            gain: 0.5,
+            ridged: false,
+            colors: vec![
+                (0.45, Color::BLACK),
+                (0.6, Color::rgb(0.15, 0.05, 0.3)),
+                (0.75, Color::rgb(0.8, 0.4, 0.6)),
+            ],
+        }
+    }
+}
+
+/// Settings for baking a set of cloudy skies from fractal noise, which differ only in how much of
+/// the sky is covered by clouds. Since the clouds are the same shape in every sky, fading between
+/// them, such as with the [`SkyboxWeatherPlugin`](crate::SkyboxWeatherPlugin) or a material's
+/// `blend_weights`, looks like clouds gathering or clearing up rather than one sky replacing
+/// another.
+///
+/// Clouds cover the sky above the horizon, thinning out towards it. The textures are baked with
+/// plain colors, so they can be lit for the time of day by tinting the material, such as with a
+/// [`DayNightCycle`](crate::DayNightCycle). [`bake`](Self::bake) returns 6-layer array textures.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{CloudCubemaps, SkyboxMaterial, SkyboxWeather, Weather};
+/// fn setup(
+///     mut textures: ResMut<Assets<Texture>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+///     mut weather: ResMut<SkyboxWeather>,
+/// ) {
+///     let clouds = CloudCubemaps {
+///         coverages: vec![0.2, 0.9],
+///         ..Default::default()
+///     };
+///     let mut skies = clouds.bake().into_iter().map(|texture| {
+///         skyboxes.add(SkyboxMaterial::from_texture(textures.add(texture)))
+///     });
+///     weather
+///         .register(Weather::Clear, skies.next().unwrap(), 10.0)
+///         .register(Weather::Overcast, skies.next().unwrap(), 30.0);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CloudCubemaps {
+    /// Width and height of each face of the textures, in pixels.
+    pub size: u32,
+    /// Seed for the noise.
+    pub seed: u64,
+    /// Number of times the largest clouds repeat around the sky, roughly.
+    pub frequency: f32,
+    /// Number of layers of noise, each with finer detail than the last.
+    pub octaves: u32,
+    /// Color of the sky between the clouds.
+    pub sky_color: Color,
+    /// Color of the clouds.
+    pub cloud_color: Color,
+    /// How gradually the edges of the clouds fade out, from `0.0` for hard edges up to about
+    /// `0.5` for wispy ones.
+    pub softness: f32,
+    /// Share of the sky covered by clouds in each baked texture, from `0.0` for a clear sky to
+    /// `1.0` for a fully overcast one.
+    pub coverages: Vec<f32>,
+}
+
+impl CloudCubemaps {
+    /// Bakes one texture for each of the `coverages`, in the same order.
+    pub fn bake(&self) -> Vec<Texture> {
+        let noise = GradientNoise::new(self.seed);
+        // Keep the noise and elevation of each pixel, so each coverage only has to remap them.
+        let samples = CubeImage::from_fn(self.size, |direction| {
+            let value = noise.fractal(direction * self.frequency, self.octaves, 2.0, 0.5, false);
+            Vec4::new(value, direction.y, 0.0, 0.0)
+        });
+        // Fractal noise is mostly between 0.3 and 0.7, so spread that out.
+        let spread = |value: f32| ((value - 0.3) / 0.4).clamp(0.0, 1.0);
+        // Pick the threshold for each coverage from the noise values actually in the sky, so that
+        // the coverage really is the share of the sky under the clouds.
+        let mut sorted: Vec<f32> = samples
+            .pixels
+            .iter()
+            .filter(|sample| sample.y > 0.0)
+            .map(|sample| spread(sample.x))
+            .collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let sky_color = Vec4::from(self.sky_color.as_linear_rgba_f32());
+        let cloud_color = Vec4::from(self.cloud_color.as_linear_rgba_f32());
+        let softness = self.softness.max(1e-3);
+        self.coverages
+            .iter()
+            .map(|&coverage| {
+                let threshold = if coverage <= 0.0 || sorted.is_empty() {
+                    f32::INFINITY
+                } else {
+                    let index = (1.0 - coverage.min(1.0)) * (sorted.len() - 1) as f32;
+                    sorted[index as usize]
+                };
+                let pixels = samples
+                    .pixels
+                    .iter()
+                    .map(|sample| {
+                        let density =
+                            ((spread(sample.x) - threshold) / softness + 0.5).clamp(0.0, 1.0);
+                        let horizon = ((sample.y - 0.02) / 0.15).clamp(0.0, 1.0);
+                        let density = smoothstep(density) * smoothstep(horizon);
+                        sky_color.lerp(cloud_color, density)
+                    })
+                    .collect();
+                CubeImage {
+                    size: self.size,
+                    pixels,
+                }
+                .into_texture()
+            })
+            .collect()
+    }
+}
+
+impl Default for CloudCubemaps {
+    fn default() -> Self {
+        Self {
+            size: 512,
+            seed: 0,
+            frequency: 3.0,
+            octaves: 6,
+            sky_color: Color::rgb(0.35, 0.55, 0.85),
+            cloud_color: Color::rgb(0.95, 0.95, 0.97),
+            softness: 0.25,
+            coverages: vec![0.0, 0.25, 0.5, 0.75, 1.0],
+        }
+    }
+}
+
+/// Smooths out the ends of a blend factor from `0.0` to `1.0`.
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Looks up `value` in a color ramp sorted by value.
+fn sample_ramp(colors: &[(f32, Vec4)], value: f32) -> Vec4 {
+    let next = colors.iter().position(|&(key, _)| key > value);
+    match next {
+        None => colors.last().map_or(Vec4::ZERO, |&(_, color)| color),
+        Some(0) => colors[0].1,
+        Some(i) => {
+            let (from, from_color) = colors[i - 1];
+            let (to, to_color) = colors[i];
+            from_color.lerp(to_color, (value - from) / (to - from))
+        }
+    }
+}
+
+/// Seeded 3D gradient noise (Perlin noise), from about `-1.0` to `1.0`.
+struct GradientNoise {
+    seed: u32,
+}
+
+impl GradientNoise {
+    /// Gradients pointing to the middle of each edge of a cube, as suggested in Perlin's improved
+    /// noise.
+    const GRADIENTS: [[f32; 3]; 12] = [
+        [1.0, 1.0, 0.0],
+        [-1.0, 1.0, 0.0],
+        [1.0, -1.0, 0.0],
+        [-1.0, -1.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [-1.0, 0.0, 1.0],
+        [1.0, 0.0, -1.0],
+        [-1.0, 0.0, -1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, -1.0, 1.0],
+        [0.0, 1.0, -1.0],
+        [0.0, -1.0, -1.0],
+    ];
+
+    fn new(seed: u64) -> Self {
+        Self {
+            seed: Rng::new(seed).next_u32(),
+        }
+    }
+
+    /// Picks the gradient at a lattice point.
+    fn gradient(&self, x: i32, y: i32, z: i32) -> Vec3 {
+        let mut hash = self.seed;
+        for &coordinate in &[x, y, z] {
+            hash = (hash ^ coordinate as u32).wrapping_mul(0x27d4_eb2d);
+            hash ^= hash >> 15;
+        }
+        Vec3::from(Self::GRADIENTS[(hash % 12) as usize])
+    }
+
+    /// Sums `octaves` layers of noise at `point`, each `lacunarity` times finer and `gain` times as
+    /// strong as the last, scaled to be from `0.0` to `1.0`.
+    fn fractal(
+        &self,
+        mut point: Vec3,
+        octaves: u32,
+        lacunarity: f32,
+        gain: f32,
+        ridged: bool,
+    ) -> f32 {
+        let (mut total, mut amplitude, mut max) = (0.0, 1.0, 0.0);
+        for octave in 0..octaves {
+            // Offset each layer so that their lattices don't line up at the origin.
+            let value = self.sample(point + Vec3::splat(octave as f32 * 17.31));
+            total += amplitude
+                * if ridged {
+                    let ridge = 1.0 - value.abs();
+                    ridge * ridge
+                } else {
+                    value * 0.5 + 0.5
+                };
+            max += amplitude;
+            amplitude *= gain;
+            point *= lacunarity;
+        }
+        if max > 0.0 {
+            total / max
+        } else {
+            0.0
+        }
+    }
+
+    fn sample(&self, point: Vec3) -> f32 {
+        let cell = point.floor();
+        let offset = point - cell;
+        let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+        // Quintic fade curve, so the noise's second derivative is continuous across cells.
+        let fade = offset
+            * offset
+            * offset
+            * (offset * (offset * 6.0 - Vec3::splat(15.0)) + Vec3::splat(10.0));
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            let corner_offset = offset - Vec3::new(dx as f32, dy as f32, dz as f32);
+            self.gradient(x + dx, y + dy, z + dz).dot(corner_offset)
+        };
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fade.x);
+        let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fade.x);
+        let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fade.x);
+        let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fade.x);
+        let y0 = lerp(x00, x10, fade.y);
+        let y1 = lerp(x01, x11, fade.y);
+        lerp(y0, y1, fade.z)
+    }
+}
@@ -55,7 +55,10 @@
 //!
 //! Skyboxes are more or less normal entities. Normal Bevy features like render layers and render
 //! pass selection should work on them, so it should be possible to have different skyboxes in
-//! different cameras using render layers.
+//! different cameras using render layers. With [`add_skybox_pass`], the same goes for cameras in
+//! other windows, such as a tool showing a sky preview next to the game view.
+//! Custom render graphs can draw skyboxes in their own passes with
+//! [`skybox_pipeline_with_descriptor`].
 //!
 //! The skybox is implemented almost entirely in shader code, so aside from the initial texture
 //! conversion (which you can do yourself if you prefer), there's no need for additional cameras or
@@ -64,14 +67,72 @@
 //! camera and skybox have no effect.
 //!
 //! In case you want your skybox to have a different orientation, the rotation compoenent of the skybox's
-//! transform *is* respected.
+//! transform *is* respected. Adding a [`SkyboxRotation`] component will slowly spin the skybox.
+//! A [`SkyboxReorientation`] smoothly turns it to a new orientation instead.
+//! [`SkyboxFollowRotation`] keeps it turned with another entity, such as a spaceship.
+//! [`SkyboxOrbitRotation`] turns the stars as seen from a planet over its day and year.
+//!
+//! For skies that change over time, a [`SkyboxMaterial`] can blend between two textures. The
+//! optional [`SkyboxDayNightPlugin`] uses this to fade between a day and night sky as the
+//! [`TimeOfDay`] advances, and moves entities marked as the sun and moon across the sky, either
+//! along a simple arc or to their real positions for a place and date (see [`astronomy`]). To switch
+//! to a different sky entirely, add a [`SkyboxTransition`] to crossfade to another material, or use
+//! the optional [`SkyboxWeatherPlugin`] to fade between skies registered for each [`Weather`].
+//! Areas of the world can have their own skies with [`SkyboxZone`]s, which are faded to as the
+//! camera enters them.
+//! A [`SkyboxColorFade`] fades the sky to a solid color and back, such as to black for a load.
+//! A [`SkyboxCameraTint`] on a camera changes the tint of the sky seen by that camera alone.
+//! For auto-exposure, a [`SkyboxLuminance`] component keeps track of how bright a sky is.
+//! A [`SkyboxAmbientLight`] keeps the scene's `AmbientLight` matched to the color of its sky.
+//! For night-vision goggles, a material can draw its sky with [`NightVision`].
+//! A [`SkyboxWaterLevel`] refracts the sky into Snell's window while the camera is under water.
+//! A material's `lower_texture` replaces the sky below the horizon, such as with the sea.
+//! Its `horizon_glow_color` brightens the horizon around the sun for sunrises and sunsets.
+//! City lights can wash out the horizon of night skies with a [`LightPollution`] glow.
+//! Distant rain and snow can be drawn streaking across the sky with [`Precipitation`].
+//! A [`SkyWind`] resource blows it in step with the wind gameplay reads.
+//! Storms can be livened up with a [`SkyboxLightning`] component, which flashes parts of the sky.
+//! Night skies can have shooting stars added with [`SkyboxMeteors`], and a realistic starfield can be
+//! baked from a star catalog with [`StarCubemap`], or from made-up stars from [`RandomStars`].
+//! Planets and other bodies can be drawn in the sky with [`SkyboxCelestialBodies`].
+//! The sun and moon themselves can be drawn with [`SkyboxSunAndMoon`], which also shows solar
+//! eclipses.
+//! For cutscenes, a [`SkyboxAnimation`] plays back keyframed changes to a skybox's tint, blend
+//! factor, and rotation.
+//! Custom effects with their own uniforms and shader code can be layered on top of the skybox shader
+//! with a [`SkyboxMaterialExtension`].
+//!
+//! With the `serde` feature enabled, materials and the other settings components can be serialized
+//! and deserialized, for example to load skies from level files. Texture handles are skipped and
+//! have to be filled in after loading.
+//! Simple sky textures can be generated at runtime with [`generate_gradient_cubemap`], and space
+//! skies with [`NebulaCubemap`]. [`CloudCubemaps`] bakes cloudy skies which can be faded between as the
+//! weather changes.
+//! Other textures can be built from raw pixels or functions with [`SkyboxTextureBuilder`].
+//! For checking a skybox's orientation, [`generate_test_pattern_cubemap`] bakes a labeled texture.
+//! [`extract_face`] copies a single face out of a skybox texture, such as for a thumbnail.
+//! Skies with their front on the wrong face can be turned around with [`rotate_skybox_y`].
+//! Smaller copies of a sky, such as for reflection probes, can be made with [`resize_skybox`].
+//! Layers such as stars over a gradient can be baked into one texture with [`composite_cubemaps`].
+//! Skybox textures can be turned into 360° panoramas with [`skybox_to_equirectangular`], and
+//! panoramas into skybox textures with [`equirectangular_to_skybox`].
+//! Over-under stereo panoramas for VR convert with [`stereo_equirectangular_to_skybox`].
+//! For planetarium domes, [`skybox_to_fisheye`] resamples a skybox into a domemaster image.
+//! Skyboxes can also be spawned from scenes using a [`SkyboxSceneTexture`].
+//! To find what part of the sky is under the cursor, use [`screen_to_sky_direction`] and
+//! [`sky_texture_coordinates`], or add [`SkyboxPickable`] to a skybox to get [`SkyboxPicked`]
+//! events when it is clicked on.
+//! To avoid a hitch the first time a skybox is drawn, [`warm_up_skybox_pipelines`] compiles its
+//! shaders during a loading screen.
+//! All skyboxes can be switched off without despawning them using [`SkyboxSettings`].
 //!
 //! # Texture Layout
 //!
 //! In order to use a Skybox, you need a properly formatted Skybox texture. Appropriate textures for
 //! `SkyboxMaterial` should have 6 identically sized square layers which make up the 6 faces of the
 //! Skybox. A helper is provided to convert a single-layer `N x 6N` image into a 6 layer image
-//! appropriate for a skybox.
+//! appropriate for a skybox, and skyboxes stored as six separate face images can be stitched
+//! together with [`SkyboxMaterial::from_faces`].
 //!
 //! This is the net of the cube that the orientation of the faces is based on. It is *not* the
 //! texture layout that is actually used for rendering.
@@ -119,6 +180,10 @@
 //! building the stacked array texture. However, since you can also rotate the skybox using the
 //! skybox entity's transform, that's probably not necessary.
 //!
+//! Skyboxes exported from tools that are Z-up or left-handed, such as Blender, Unreal Engine, or
+//! Unity, put their faces on other axes. Rather than shuffling their faces, insert the matching
+//! [`SkyboxConvention`] resource and the skybox will sample them in that convention.
+//!
 //! # Maintenance of this Crate
 //!
 //! Bevy is a cool project and I am excited for it to succeed. However, I don't necessarily have
@@ -135,17 +200,23 @@
 //! working on main, and need to modify this crate to work with the latest `HEAD`, I recommend
 //! forking and then sending me a pull request once Bevy publishes an updated version.
 //!
-//! In terms of features, I would consider this project largely feature-complete as-is. Texture
-//! packaging seems to be outside the scope of features supported by Bevy, so I'm not going to add
-//! tools to automate building textures for skyboxes. This crate also isn't intended to support any
-//! kind of dynamic skyboxes, so there doesn't seem to be much more that needs to be done besides
-//! keeping up with latest versions of Bevy. However, if you have any ideas for new features or API
-//! changes, I'm happy to hear them.
+//! Besides drawing cubemap skyboxes, the crate covers animating skies over time, such as day/night
+//! cycles, weather, and effects drawn over the sky, and building skybox textures at runtime, from
+//! generators, panoramas, and other skyboxes. Beyond that, the main ongoing work is keeping up with
+//! the latest versions of Bevy. If you have any ideas for new features or API changes, I'm happy to
+//! hear them.
 //!
 //! # Disclaimer
 //!
 //! This is not an officially supported Google product.
 
+// The `Bundle` derive forgets each component after moving it out, which clippy flags for components
+// that don't implement `Drop`.
+#![allow(clippy::forget_non_drop)]
+
+use bevy::asset::HandleId;
+use bevy::core::AsBytes;
+use bevy::ecs::schedule::SystemLabel;
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
 use bevy::render::pipeline::{
@@ -154,12 +225,98 @@ use bevy::render::pipeline::{
     PrimitiveState, PrimitiveTopology, RenderPipeline, StencilFaceState, StencilState,
 };
 use bevy::render::render_graph::base::MainPass;
-use bevy::render::render_graph::{base, AssetRenderResourcesNode, RenderGraph};
-use bevy::render::renderer::RenderResources;
+use bevy::render::render_graph::{
+    base, AssetRenderResourcesNode, NodeLabel, RenderGraph, RenderGraphError,
+};
+use bevy::render::renderer::{
+    RenderResource, RenderResourceIterator, RenderResourceType, RenderResources,
+};
 use bevy::render::shader::{asset_shader_defs_system, ShaderDefs, ShaderStage, ShaderStages};
-use bevy::render::texture::TextureFormat;
+use bevy::render::texture::{TextureDimension, TextureFormat};
+use bevy::utils::HashSet;
+
+mod animation;
+pub mod astronomy;
+mod camera;
+mod celestial;
+mod convention;
+mod cube;
+mod day_night;
+mod extension;
+mod face;
+mod generate;
+mod light_pollution;
+mod lightning;
+mod luminance;
+mod mesh;
+mod meteors;
+mod night_vision;
+mod panorama;
+mod precipitation;
+mod random;
+mod rotation;
+mod scene;
+mod screen;
+mod settings;
+mod stars;
+mod transition;
+mod underwater;
+mod warm_up;
+mod weather;
+mod wind;
+mod zone;
+
+pub use animation::{Keyframe, SkyboxAnimation};
+pub use camera::SkyboxCameraTint;
+pub use celestial::{CelestialBody, CelestialPosition, SkyboxCelestialBodies};
+pub use convention::SkyboxConvention;
+pub use day_night::{
+    DayNightCycle, SkyColorKey, SkyColorRamp, SkyboxDayNightPlugin, SkyboxMoon, SkyboxSun,
+    SkyboxSunAndMoon, SunFlare, TimeOfDay,
+};
+pub use extension::{SkyboxExtensionPipeline, SkyboxExtensionPlugin, SkyboxMaterialExtension};
+pub use face::{
+    composite_cubemaps, extract_face, resize_skybox, rotate_skybox_y, CubeFace, ResizeFilter,
+};
+pub use generate::{
+    generate_gradient_cubemap, generate_test_pattern_cubemap, CloudCubemaps, NebulaCubemap,
+    SkyboxTextureBuilder,
+};
+pub use light_pollution::{LightPollution, MAX_CITY_GLOWS};
+pub use lightning::{LightningFlash, SkyboxLightning};
+pub use luminance::{
+    average_sky_color, average_sky_luminance, SkyboxAmbientLight, SkyboxLuminance,
+};
+pub use meteors::SkyboxMeteors;
+pub use night_vision::NightVision;
+pub use panorama::{
+    equirectangular_to_skybox, skybox_to_equirectangular, skybox_to_fisheye,
+    stereo_equirectangular_to_skybox,
+};
+pub use precipitation::Precipitation;
+pub use rotation::{
+    SkyboxFollowRotation, SkyboxOrbitRotation, SkyboxReorientation, SkyboxRotation,
+};
+pub use scene::SkyboxSceneTexture;
+pub use screen::{screen_to_sky_direction, sky_texture_coordinates, SkyboxPickable, SkyboxPicked};
+pub use settings::SkyboxSettings;
+pub use stars::{RandomStars, Star, StarCubemap};
+pub use transition::{FadeEasing, SkyboxColorFade, SkyboxTransition};
+pub use underwater::{SkyboxWaterLevel, Underwater};
+pub use warm_up::{warm_up_skybox_pipelines, SkyboxWarmUpPipelines};
+pub use weather::{SkyboxWeather, SkyboxWeatherPlugin, Weather, WeatherSky, WeatherSkybox};
+pub use wind::SkyWind;
+pub use zone::{SkyboxZone, ZoneShape, ZoneSkybox};
 
-/// Configures the skybox render pipeline and support for [`SkyboxMaterial`]. Also sets up the system for [`
+/// Configures the skybox render pipeline and support for [`SkyboxMaterial`]. Also sets up the systems
+/// for [`SkyboxTextureConversion`], [`SkyboxRotation`], [`SkyboxReorientation`],
+/// [`SkyboxOrbitRotation`], [`SkyboxTransition`], [`SkyboxColorFade`], [`SkyboxAnimation`],
+/// [`SkyboxLightning`], [`SkyboxMeteors`], [`SkyboxCelestialBodies`], [`SkyboxZone`],
+/// [`SkyboxLuminance`], and [`SkyboxAmbientLight`].
+///
+/// The systems are labeled with [`SkyboxSystem`], so other systems can be ordered around them.
+/// All skyboxes can be switched off at runtime with the [`SkyboxSettings`] resource, and the
+/// [`SkyboxConvention`] resource sets the coordinate convention skybox textures are sampled in.
 pub struct SkyboxPlugin;
 
 impl Plugin for SkyboxPlugin {
@@ -169,10 +326,52 @@ impl Plugin for SkyboxPlugin {
                 CoreStage::PostUpdate,
                 asset_shader_defs_system::<SkyboxMaterial>.system(),
             )
+            .init_resource::<SkyboxSettings>()
+            .init_resource::<SkyboxConvention>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                settings::apply_skybox_settings.system(),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, luminance::measure_skyboxes.system())
             .init_resource::<SkyboxTextureConversion>()
-            .add_system(convert_skyboxes.system());
+            .add_system(convert_skyboxes.system().label(SkyboxSystem::Convert))
+            .add_event::<LightningFlash>()
+            .add_system_set(
+                SystemSet::new()
+                    .label(SkyboxSystem::Animate)
+                    .with_run_criteria(settings::skyboxes_enabled.system())
+                    .with_system(rotation::rotate_skyboxes.system())
+                    .with_system(rotation::reorient_skyboxes.system())
+                    .with_system(rotation::follow_rotations.system())
+                    .with_system(rotation::orbit_rotations.system())
+                    .with_system(
+                        transition::run_skybox_transitions
+                            .system()
+                            .label(transition::TransitionSystem::Run),
+                    )
+                    .with_system(transition::run_color_fades.system())
+                    .with_system(zone::switch_zone_skies.system())
+                    .with_system(night_vision::animate_night_vision.system())
+                    .with_system(precipitation::animate_precipitation.system())
+                    .with_system(underwater::apply_water_levels.system())
+                    .with_system(animation::run_skybox_animations.system())
+                    .with_system(lightning::run_lightning.system())
+                    .with_system(meteors::run_meteors.system())
+                    .with_system(celestial::place_celestial_bodies.system()),
+            )
+            .add_system(mesh::check_skybox_meshes.system())
+            .register_type::<SkyboxSceneTexture>()
+            .add_system(
+                scene::spawn_scene_skyboxes
+                    .system()
+                    .before(SkyboxSystem::Convert),
+            )
+            .add_event::<SkyboxPicked>()
+            .add_system(screen::pick_skyboxes.system())
+            .init_resource::<SkyboxWarmUpPipelines>()
+            .add_system(warm_up::finish_warm_ups.system());
         add_skybox_graph(app.world_mut());
-        add_skybox_mesh(&mut *app.world_mut().get_resource_mut().unwrap());
+        add_skybox_mesh(&mut app.world_mut().get_resource_mut().unwrap());
 
         // add default SkyboxMaterial
         let mut materials = app
@@ -189,6 +388,28 @@ impl Plugin for SkyboxPlugin {
     }
 }
 
+/// Labels for the systems added by the [`SkyboxPlugin`] and the other skybox plugins, for ordering
+/// other systems relative to them. All of them run in [`CoreStage::Update`].
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxPlugin, SkyboxSystem};
+/// # fn generate_lighting() {}
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     // Sees skybox textures in the frame they are converted.
+///     .add_system(generate_lighting.system().after(SkyboxSystem::Convert));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SkyboxSystem {
+    /// Converts textures queued with [`SkyboxTextureConversion`] once they load.
+    Convert,
+    /// Updates skybox materials and transforms from the components which animate them, such as
+    /// [`SkyboxRotation`], [`SkyboxTransition`], [`DayNightCycle`], and [`SkyboxWeather`].
+    Animate,
+}
+
 /// Bundle for spawning Skybox entities. Note that you should be able to use defaults for everything
 /// besides `material`. The only other field you may want to touch is `transform` which can be used
 /// to rotate the skybox if desired. Translations applied to skyboxes are ignored.
@@ -197,6 +418,11 @@ impl Plugin for SkyboxPlugin {
 /// property except the `material` and occasionally `transform` (if you want to rotate the skybox
 /// from its default orientation).
 ///
+/// Skyboxes never need to be excluded from frustum culling. Even though the skybox mesh is a small
+/// cube at the origin, Bevy only culls sprites (by marking them with
+/// [`OutsideFrustum`](bevy::render::draw::OutsideFrustum)), so the skybox is always drawn no matter
+/// how far the camera is from the origin.
+///
 /// ```no_run
 /// # use bevy::prelude::*;
 /// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, SkyboxPlugin, SkyboxTextureConversion};
@@ -222,18 +448,29 @@ pub struct SkyboxBundle {
     /// Material to use for the skybox. Defaults to a garish pink. In most usage this should be the
     /// only field you need to set.
     pub material: Handle<SkyboxMaterial>,
-    /// Mesh to use for the skybox. Defaults to [`SKYBOX_MESH_HANDLE`], which is a unit cube. You
-    /// shouldn't ever need to use any other mesh. Because of how cubemap sampling works, probably
-    /// any mesh that completely surrounds the camera would work equally well, but only the unit
-    /// cube is officially supported by this crate.
+    /// Mesh to use for the skybox. Defaults to [`SKYBOX_MESH_HANDLE`], which is a unit cube.
+    /// [`SKYBOX_DOME_MESH_HANDLE`] and [`SKYBOX_SPHERE_MESH_HANDLE`] are also provided, and any
+    /// mesh can be picked with [`SkyboxBundle::with_mesh`].
+    ///
+    /// Custom meshes, such as domes modeled for a particular game, must completely surround the
+    /// origin, since the sky is sampled in the direction of each vertex from the origin; the
+    /// [`SkyboxPlugin`] logs a warning for meshes whose bounds don't. Their size and shape don't
+    /// otherwise matter: the skybox shader ignores translation and always draws the mesh at the
+    /// far plane, so it stays behind everything else no matter how big it is. Like the built-in
+    /// meshes, their faces should point outwards, or be drawn with a pipeline from
+    /// [`skybox_pipeline_with_primitive`] that culls accordingly.
     pub mesh: Handle<Mesh>,
-    /// Marker to draw the skybox in the main pass.
+    /// Marker to draw the skybox in the main pass. Skyboxes are only drawn in passes whose marker
+    /// component they have, so as long as this is the only pass marker on the skybox it won't be
+    /// drawn into any other passes (such as custom shadow passes). Bevy itself doesn't render
+    /// shadows yet, so there are no shadow caster or receiver components to add.
     pub main_pass: MainPass,
     /// This is included in every type that can be drawn. Honestly not sure what it does.
     pub draw: Draw,
     /// This is included in every type that can be drawn. Can be used to hide the skybox.
     pub visible: Visible,
-    /// Needs to be configured to use the skybox render pipeline.
+    /// Needs to be configured to use the skybox render pipeline. See
+    /// [`SkyboxBundle::with_pipeline`] to use a different one.
     pub render_pipelines: RenderPipelines,
     /// Transform can be used to manipulate the rotation of the skybox.
     pub transform: Transform,
@@ -252,6 +489,21 @@ impl SkyboxBundle {
             ..Default::default()
         }
     }
+
+    /// Draws the skybox with `mesh` instead of the default cube, such as
+    /// [`SKYBOX_SPHERE_MESH_HANDLE`].
+    pub fn with_mesh(mut self, mesh: Handle<Mesh>) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// Draws the skybox with `pipeline` instead of the default skybox pipeline. The pipeline must
+    /// use the skybox shaders; see [`skybox_pipeline_with_primitive`].
+    pub fn with_pipeline(mut self, pipeline: Handle<PipelineDescriptor>) -> Self {
+        self.render_pipelines =
+            RenderPipelines::from_pipelines(vec![RenderPipeline::new(pipeline)]);
+        self
+    }
 }
 
 impl Default for SkyboxBundle {
@@ -277,6 +529,30 @@ impl Default for SkyboxBundle {
 /// tint the skybox. When creating a new material, the default color is [`Color::WHITE`] which will
 /// have no effect on the texture color.
 ///
+/// A second texture, `blend_texture`, can be supplied to crossfade between two skies. The
+/// `blend_factor` controls how much of each texture is used: `0.0` shows only `texture` and `1.0`
+/// shows only `blend_texture`. The [`SkyboxDayNightPlugin`] uses this to fade between day and
+/// night skies. For more than two skies, up to two more textures can be added with
+/// `blend_texture_2` and `blend_texture_3`, and mixed with `blend_weights`. A `lower_texture` can
+/// be shown below the horizon instead, for a different sky above and below.
+///
+/// The `flash_*` fields brighten a region of the sky, which [`SkyboxLightning`] uses for lightning
+/// flashes. Similarly, the `meteor_*` fields draw a streak across the sky, which [`SkyboxMeteors`]
+/// uses for shooting stars. Up to [`MAX_SKY_DISCS`] small discs, such as planets, can be drawn in
+/// the sky with `sky_discs`; see [`SkyboxCelestialBodies`] for placing them automatically.
+///
+/// The `sun_*` and `moon_*` fields draw the discs of the sun and moon, with the moon in front, so
+/// solar eclipses can be shown, along with the sun's corona through `corona_color`. The
+/// [`SkyboxSunAndMoon`] component sets them from the [`TimeOfDay`].
+///
+/// The `haze_*` fields fade the sky towards a color near the horizon, which makes outdoor scenes
+/// look more distant even with a static sky texture. The horizon is always level in world space,
+/// so it isn't affected by rotating the skybox. Similarly, the `ground_*` fields fade the sky into
+/// a ground color below the horizon, and the `horizon_glow_*` fields brighten the horizon around
+/// the sun's bearing, which sells a sunrise or sunset far better than haze alone. Night skies over
+/// towns can have the glow of city lights added with `light_pollution`, and rain or snow can be
+/// seen falling in the distance with `precipitation`.
+///
 /// It is also possible to use a skybox texture with only a [`Color`]. One reason you might want to
 /// do this is that (at time of writing) Bevy does not seem to antialias against the window
 /// [`ClearColor`] properly, instead antialiasing with white for objects that have not other 3d
@@ -284,9 +560,16 @@ impl Default for SkyboxBundle {
 /// window clear color. To avoid this, you could spawn a skybox using only a color. Since the skybox
 /// is a 3d rendered object, antialiasing against it works properly.
 ///
+/// Like other Bevy assets, textures are only kept loaded while something holds a strong handle to
+/// them, such as the ones returned by [`AssetServer::load`] and [`Assets::add`]. The material holds
+/// its textures' handles as they're given to it, so a texture loaded only for a skybox stays
+/// loaded as long as its material does if the material has the strong handle, but not if it only
+/// has a weak one.
+///
 /// Skyboxes should generally be spawned using [`SkyboxBundle`], and you can see that type for info
 /// on what components are used with this material.
-#[derive(RenderResources, Debug, ShaderDefs, TypeUuid)]
+#[derive(Debug, Clone, ShaderDefs, TypeUuid)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // UUID5 generated by first creating a URL-namespaced UUID5 for
 // "https://github.com/google/bevy_skybox_cubemap" (24291f52-ea01-574a-b6ae-3d8182f6086b) then using
 // that as the namespace with `bevy_skybox_cubemap::SkyboxMaterial` as the name.
@@ -298,8 +581,157 @@ pub struct SkyboxMaterial {
     /// Texture to use for the skybox. This must be a an aray texture with 6 layers which are all
     /// square and the same size. See [the crate overview](crate) for details on the required layer
     /// order and how to get a texture in this format.
+    #[cfg_attr(feature = "serde", serde(skip))]
     #[shader_def]
     pub texture: Option<Handle<Texture>>,
+    /// How far to blend from `texture` towards `blend_texture`, from `0.0` to `1.0`. Has no effect
+    /// if `blend_texture` is not set. If `blend_texture` is set but `texture` is not, the sky
+    /// blends from plain white (tinted by `color`) towards `blend_texture`.
+    pub blend_factor: f32,
+    /// Second texture to blend with `texture`. Must be in the same format as `texture`; see [the
+    /// crate overview](crate) for details.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[shader_def]
+    pub blend_texture: Option<Handle<Texture>>,
+    /// Third texture, only used when `blend_weights` is set.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[shader_def]
+    pub blend_texture_2: Option<Handle<Texture>>,
+    /// Fourth texture, only used when `blend_weights` is set.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[shader_def]
+    pub blend_texture_3: Option<Handle<Texture>>,
+    /// Texture to show below the horizon in place of the other textures, such as the sea for a
+    /// sailing game or an abyss for a flying one. Must be in the same format as `texture`, and
+    /// shows the same `layer`. The horizon is level in world space and moved by `horizon_offset`,
+    /// like the ground.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[shader_def]
+    pub lower_texture: Option<Handle<Texture>>,
+    /// Angle in radians around the horizon over which the sky blends into `lower_texture`.
+    pub lower_texture_softness: f32,
+    /// Weights of `texture`, `blend_texture`, `blend_texture_2`, and `blend_texture_3`, for
+    /// blending between up to four skies. The weights don't need to add up to one. When set,
+    /// `blend_factor` is ignored.
+    pub blend_weights: Option<Vec4>,
+    /// Which sky to show from textures holding several skies, such as those converted with
+    /// [`SkyboxTextureConversion::make_multi_sky_array`]. Sky `n` is made of layers `6 * n` to
+    /// `6 * n + 5`, in the usual order. Applies to all of the material's textures, so switching
+    /// skies is just a matter of changing this.
+    pub layer: u32,
+    /// World-space direction of the center of the flash. Doesn't need to be normalized.
+    pub flash_direction: Vec3,
+    /// Angular radius of the flash in radians. The flash fades out smoothly from its center to this
+    /// angle.
+    pub flash_spread: f32,
+    /// Brightness of the flash. `0.0` turns the flash off.
+    pub flash_intensity: f32,
+    /// Color of the flash. The flash is added on top of the sky rather than tinted by `color`.
+    pub flash_color: Color,
+    /// World-space direction of the front of the meteor streak. Doesn't need to be normalized.
+    pub meteor_head: Vec3,
+    /// World-space direction of the end of the meteor's trail. The streak is drawn along the
+    /// shortest arc from here to `meteor_head`, getting brighter towards the head.
+    pub meteor_tail: Vec3,
+    /// Angular half-width of the meteor streak in radians.
+    pub meteor_width: f32,
+    /// Brightness of the meteor streak. `0.0` turns it off.
+    pub meteor_brightness: f32,
+    /// Color of the meteor streak. Like the flash, this is added on top of the sky.
+    pub meteor_color: Color,
+    /// Small discs to draw in the sky, such as planets. Only the first [`MAX_SKY_DISCS`] are drawn.
+    pub sky_discs: Vec<SkyDisc>,
+    /// Color of the haze near the horizon. The alpha sets how strongly the sky fades towards this
+    /// color at the horizon; `0.0` turns the haze off.
+    pub haze_color: Color,
+    /// Angle in radians above and below the horizon within which the haze is at full strength.
+    pub haze_height: f32,
+    /// Angle in radians beyond `haze_height` over which the haze fades out.
+    pub haze_softness: f32,
+    /// Color of the glow along the horizon around the sun's bearing, from `sun_direction`, which is
+    /// added on top of the sky and the haze. Black turns the glow off. The glow follows the sun's
+    /// bearing even when the sun is below the horizon, so fade this out as the sun sets further.
+    pub horizon_glow_color: Color,
+    /// Angle in radians around the sun's bearing over which the horizon glow fades out to either
+    /// side.
+    pub horizon_glow_spread: f32,
+    /// Angle in radians from the horizon over which the horizon glow fades out upwards and
+    /// downwards.
+    pub horizon_glow_height: f32,
+    /// Color of the ground below the horizon. The alpha sets how strongly the sky fades into the
+    /// ground; `0.0` turns the ground off. Usually paired with [`SKYBOX_DOME_MESH_HANDLE`].
+    pub ground_color: Color,
+    /// Angle in radians below the horizon over which the sky fades into the ground.
+    pub ground_softness: f32,
+    /// Colors to tint each face of the skybox with, in the same order as the texture's layers.
+    /// Multiplied with the sky color before any effects are added. Useful for checking how faces
+    /// are mapped, or for darkening the bottom face of a sky without editing its texture.
+    pub face_tints: Option<[Color; 6]>,
+    /// Bitmask of faces to leave out, for debugging seams and face orientation: bit `n` hides the
+    /// face sampled from layer `n`, letting whatever is behind the skybox show through. `0` draws
+    /// every face.
+    pub hidden_faces: u8,
+    /// Shifts the horizon of the sky texture, the haze, and the ground down (positive) or up
+    /// (negative), by adding this to the vertical component of each direction before the sky is
+    /// sampled. Useful when the camera is far above the horizon the texture was made for, such as
+    /// in an airplane, where the real horizon dips below eye level. `0.1` lowers the horizon by
+    /// about six degrees.
+    pub horizon_offset: f32,
+    /// Color temperature, in Kelvin, of the light the sky texture is shown in, like a camera's white
+    /// balance. Lower values make it warmer and higher values cooler, without changing its overall
+    /// brightness, so the same texture can look warm at sunset and cool at noon. `6500.0` leaves
+    /// the texture's colors as they are. Skies with only a color are tinted too.
+    pub color_temperature: f32,
+    /// Angle in radians to rotate the hue of the sky textures by, for palette swaps such as an alien
+    /// planet's sky. `0.0` leaves the hues alone, and `TAU / 3.0` turns red to green, green to
+    /// blue, and blue to red. Grays, and so skies with only a color, aren't affected.
+    pub hue_shift: f32,
+    /// Color to fade the whole sky to, including the sun, moon, and other effects drawn on top of
+    /// it. The alpha sets how far the sky is faded, so the default of transparent black leaves it
+    /// alone. A [`SkyboxColorFade`] animates this to fade to black or white and back.
+    pub fade_color: Color,
+    /// Draws the sky as seen through night-vision goggles. See [`NightVision`].
+    pub night_vision: Option<NightVision>,
+    /// Adds the glow of city lights over the horizon. See [`LightPollution`].
+    pub light_pollution: Option<LightPollution>,
+    /// Draws rain or snow falling across the sky. See [`Precipitation`].
+    pub precipitation: Option<Precipitation>,
+    /// Draws the sky as seen from under water, squeezed into Snell's window. See [`Underwater`] and
+    /// [`SkyboxWaterLevel`].
+    pub underwater: Option<Underwater>,
+    /// World-space direction of the sun's disc. Doesn't need to be normalized.
+    pub sun_direction: Vec3,
+    /// Angular radius of the sun's disc in radians.
+    pub sun_angular_radius: f32,
+    /// Color of the sun's disc. Like the flash, this is added on top of the sky. Black turns the
+    /// sun's disc off.
+    pub sun_color: Color,
+    /// World-space direction of the moon's disc. Doesn't need to be normalized.
+    pub moon_direction: Vec3,
+    /// Angular radius of the moon's disc in radians. `0.0` turns the moon's disc off.
+    pub moon_angular_radius: f32,
+    /// Color of the moon's disc, which is added on top of the sky. The alpha sets how much the moon
+    /// hides the sky behind it. The moon always hides the sun and its corona, whatever the alpha.
+    pub moon_color: Color,
+    /// Color of the corona glowing around the sun, which is added on top of the sky. It's usually
+    /// only visible during a total solar eclipse. Black turns the corona off.
+    pub corona_color: Color,
+}
+
+/// Maximum number of [`SkyDisc`]s a [`SkyboxMaterial`] can draw.
+pub const MAX_SKY_DISCS: usize = 4;
+
+/// Small disc drawn in the sky by a [`SkyboxMaterial`], such as a planet or a distant space station.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyDisc {
+    /// World-space direction of the center of the disc. Doesn't need to be normalized.
+    pub direction: Vec3,
+    /// Angular radius of the disc in radians. Real planets are far smaller than a pixel, so this
+    /// should be exaggerated to make them visible.
+    pub angular_radius: f32,
+    /// Color of the disc. Like the flash, this is added on top of the sky.
+    pub color: Color,
 }
 
 impl SkyboxMaterial {
@@ -312,6 +744,41 @@ impl SkyboxMaterial {
         }
     }
 
+    /// Creates a `SkyboxMaterial` with a texture stitched together from six separate face images
+    /// once they've loaded, using [`SkyboxTextureConversion::assemble_faces`], which explains how
+    /// the faces are laid out. The material can be used right away, but isn't drawn until the
+    /// texture is ready.
+    ///
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, SkyboxTextureConversion};
+    /// fn setup(
+    ///     mut commands: Commands,
+    ///     asset_server: Res<AssetServer>,
+    ///     textures: Res<Assets<Texture>>,
+    ///     mut conversion: ResMut<SkyboxTextureConversion>,
+    ///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+    /// ) {
+    ///     let faces = [
+    ///         asset_server.load("sky/right.png"),
+    ///         asset_server.load("sky/left.png"),
+    ///         asset_server.load("sky/top.png"),
+    ///         asset_server.load("sky/bottom.png"),
+    ///         asset_server.load("sky/back.png"),
+    ///         asset_server.load("sky/front.png"),
+    ///     ];
+    ///     let material = SkyboxMaterial::from_faces(faces, &textures, &mut conversion);
+    ///     commands.spawn_bundle(SkyboxBundle::new(skyboxes.add(material)));
+    /// }
+    /// ```
+    pub fn from_faces(
+        faces: [Handle<Texture>; 6],
+        textures: &Assets<Texture>,
+        conversion: &mut SkyboxTextureConversion,
+    ) -> Self {
+        Self::from_texture(conversion.assemble_faces(faces, textures))
+    }
+
     /// Creates a `SkyboxMaterial` with only a color. This could be used in place of [`ClearColor`]
     /// if `ClearColor` is giving you issues with antialiasing. Otherwise it's not all that useful.
     pub fn from_color(color: Color) -> Self {
@@ -330,10 +797,266 @@ impl Default for SkyboxMaterial {
             // the texture color.
             color: Color::WHITE,
             texture: None,
+            blend_factor: 0.0,
+            blend_texture: None,
+            blend_texture_2: None,
+            blend_texture_3: None,
+            blend_weights: None,
+            lower_texture: None,
+            lower_texture_softness: 0.02,
+            layer: 0,
+            flash_direction: Vec3::Y,
+            flash_spread: 0.5,
+            flash_intensity: 0.0,
+            flash_color: Color::WHITE,
+            meteor_head: Vec3::Y,
+            meteor_tail: Vec3::Y,
+            meteor_width: 0.002,
+            meteor_brightness: 0.0,
+            meteor_color: Color::WHITE,
+            sky_discs: Vec::new(),
+            haze_color: Color::rgba(1.0, 1.0, 1.0, 0.0),
+            haze_height: 0.05,
+            haze_softness: 0.2,
+            horizon_glow_color: Color::BLACK,
+            horizon_glow_spread: 0.6,
+            horizon_glow_height: 0.15,
+            ground_color: Color::rgba(0.3, 0.3, 0.3, 0.0),
+            ground_softness: 0.05,
+            face_tints: None,
+            hidden_faces: 0,
+            horizon_offset: 0.0,
+            color_temperature: NEUTRAL_COLOR_TEMPERATURE,
+            hue_shift: 0.0,
+            fade_color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+            night_vision: None,
+            light_pollution: None,
+            precipitation: None,
+            underwater: None,
+            sun_direction: Vec3::Y,
+            sun_angular_radius: 0.00465,
+            sun_color: Color::BLACK,
+            moon_direction: Vec3::Y,
+            moon_angular_radius: 0.0,
+            moon_color: Color::WHITE,
+            corona_color: Color::BLACK,
         }
     }
 }
 
+/// Linear color to multiply the sky texture by for [`SkyboxMaterial::color_temperature`].
+fn white_balance(temperature: f32) -> Vec3 {
+    if temperature == NEUTRAL_COLOR_TEMPERATURE {
+        return Vec3::ONE;
+    }
+    let linear = |temperature| {
+        Vec4::from(stars::temperature_color(temperature).as_linear_rgba_f32()).truncate()
+    };
+    let balance = linear(temperature.clamp(1000.0, 40000.0)) / linear(NEUTRAL_COLOR_TEMPERATURE);
+    // Keep the luminance the same, so only the hue changes.
+    balance / balance.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
+
+/// Names of the [`SkyboxMaterial`]'s bindings in the skybox shaders.
+const SKYBOX_MATERIAL_RENDER_RESOURCE_NAMES: &[&str] = &[
+    "SkyboxMaterial_color",
+    "SkyboxMaterial_texture",
+    "SkyboxMaterial_blend_texture",
+    "SkyboxMaterial_params",
+    "SkyboxMaterial_blend_texture_2",
+    "SkyboxMaterial_blend_texture_3",
+    "SkyboxMaterial_lower_texture",
+];
+
+/// Number of `vec4`s in the `SkyboxMaterial_params` uniform block.
+const SKYBOX_MATERIAL_PARAMS_LEN: usize = 39 + 2 * MAX_SKY_DISCS;
+
+/// Color temperature at which [`SkyboxMaterial::color_temperature`] has no effect, roughly that of
+/// daylight.
+const NEUTRAL_COLOR_TEMPERATURE: f32 = 6500.0;
+
+impl SkyboxMaterial {
+    /// Packs the scalar parameters of the material for the `SkyboxMaterial_params` uniform block. The
+    /// order must match the block in `skybox.frag`.
+    fn params(&self) -> [Vec4; SKYBOX_MATERIAL_PARAMS_LEN] {
+        let flash_color: Vec4 = self.flash_color.as_linear_rgba_f32().into();
+        let meteor_color: Vec4 = self.meteor_color.as_linear_rgba_f32().into();
+        let haze_color: Vec4 = self.haze_color.as_linear_rgba_f32().into();
+        let horizon_glow_color: Vec4 = self.horizon_glow_color.as_linear_rgba_f32().into();
+        let sun_color: Vec4 = self.sun_color.as_linear_rgba_f32().into();
+        let moon_color: Vec4 = self.moon_color.as_linear_rgba_f32().into();
+        let corona_color: Vec4 = self.corona_color.as_linear_rgba_f32().into();
+        let ground_color: Vec4 = self.ground_color.as_linear_rgba_f32().into();
+        let fade_color: Vec4 = self.fade_color.as_linear_rgba_f32().into();
+        let night_vision_color: Vec4 = self
+            .night_vision
+            .as_ref()
+            .map_or(Vec4::ZERO, |night_vision| {
+                night_vision.color.as_linear_rgba_f32().into()
+            });
+        let (underwater_tint, underwater_color): (Vec4, Vec4) = match &self.underwater {
+            Some(underwater) => (
+                underwater.tint.as_linear_rgba_f32().into(),
+                underwater.water_color.as_linear_rgba_f32().into(),
+            ),
+            None => (Vec4::ONE, Vec4::ZERO),
+        };
+        let light_pollution = self
+            .light_pollution
+            .as_ref()
+            .map_or([Vec4::ZERO; 3], LightPollution::params);
+        let precipitation = self
+            .precipitation
+            .as_ref()
+            .map_or([Vec4::ZERO; 3], Precipitation::params);
+        let mut face_tints = [Vec4::ONE; 6];
+        if let Some(tints) = &self.face_tints {
+            for (slot, tint) in face_tints.iter_mut().zip(tints) {
+                *slot = tint.as_linear_rgba_f32().into();
+            }
+        }
+        let fixed = [
+            Vec4::new(
+                self.blend_factor,
+                if self.blend_weights.is_some() {
+                    1.0
+                } else {
+                    0.0
+                },
+                self.layer as f32,
+                self.hidden_faces as f32,
+            ),
+            self.flash_direction
+                .normalize_or_zero()
+                .extend(self.flash_spread.cos()),
+            (flash_color.truncate() * self.flash_intensity).extend(0.0),
+            self.meteor_head
+                .normalize_or_zero()
+                .extend(self.meteor_width),
+            self.meteor_tail.normalize_or_zero().extend(0.0),
+            (meteor_color.truncate() * self.meteor_brightness).extend(0.0),
+            haze_color,
+            Vec4::new(self.haze_height, self.haze_softness, 0.0, 0.0),
+            self.blend_weights.unwrap_or(Vec4::ZERO),
+            self.sun_direction
+                .normalize_or_zero()
+                .extend(self.sun_angular_radius),
+            sun_color.truncate().extend(0.0),
+            self.moon_direction
+                .normalize_or_zero()
+                .extend(self.moon_angular_radius),
+            moon_color,
+            corona_color.truncate().extend(0.0),
+            ground_color,
+            Vec4::new(self.ground_softness, 0.0, 0.0, 0.0),
+            face_tints[0],
+            face_tints[1],
+            face_tints[2],
+            face_tints[3],
+            face_tints[4],
+            face_tints[5],
+            Vec4::new(self.horizon_offset, 0.0, 0.0, 0.0),
+            white_balance(self.color_temperature).extend(self.hue_shift),
+            fade_color,
+            match &self.night_vision {
+                Some(night_vision) => Vec4::new(
+                    1.0,
+                    night_vision.gain,
+                    night_vision.noise,
+                    night_vision.noise_seed,
+                ),
+                None => Vec4::ZERO,
+            },
+            night_vision_color,
+            match &self.underwater {
+                Some(underwater) => Vec4::new(
+                    underwater.refractive_index,
+                    underwater.edge_softness,
+                    0.0,
+                    0.0,
+                ),
+                None => Vec4::ZERO,
+            },
+            underwater_tint,
+            underwater_color,
+            Vec4::new(self.lower_texture_softness, 0.0, 0.0, 0.0),
+            horizon_glow_color.truncate().extend(0.0),
+            Vec4::new(self.horizon_glow_spread, self.horizon_glow_height, 0.0, 0.0),
+            light_pollution[0],
+            light_pollution[1],
+            light_pollution[2],
+            precipitation[0],
+            precipitation[1],
+            precipitation[2],
+        ];
+        let mut params = [Vec4::ZERO; SKYBOX_MATERIAL_PARAMS_LEN];
+        params[..fixed.len()].copy_from_slice(&fixed);
+        for (slots, disc) in params[fixed.len()..]
+            .chunks_exact_mut(2)
+            .zip(&self.sky_discs)
+        {
+            let color: Vec4 = disc.color.as_linear_rgba_f32().into();
+            slots[0] = disc
+                .direction
+                .normalize_or_zero()
+                .extend(disc.angular_radius.cos());
+            slots[1] = color.truncate().extend(0.0);
+        }
+        params
+    }
+}
+
+// Every uniform buffer of a material uses up one of the 8 uniform buffers with dynamic offsets that
+// wgpu allows per pipeline, so rather than deriving `RenderResources` with a separate uniform for
+// each field, all of the scalar parameters are packed into a single `SkyboxMaterial_params` block.
+// The material itself provides the bytes of that block.
+impl RenderResources for SkyboxMaterial {
+    fn render_resources_len(&self) -> usize {
+        SKYBOX_MATERIAL_RENDER_RESOURCE_NAMES.len()
+    }
+
+    fn get_render_resource(&self, index: usize) -> Option<&dyn RenderResource> {
+        match index {
+            0 => Some(&self.color),
+            1 => Some(&self.texture),
+            2 => Some(&self.blend_texture),
+            3 => Some(self),
+            4 => Some(&self.blend_texture_2),
+            5 => Some(&self.blend_texture_3),
+            6 => Some(&self.lower_texture),
+            _ => None,
+        }
+    }
+
+    fn get_render_resource_name(&self, index: usize) -> Option<&str> {
+        SKYBOX_MATERIAL_RENDER_RESOURCE_NAMES.get(index).copied()
+    }
+
+    fn iter(&self) -> RenderResourceIterator<'_> {
+        RenderResourceIterator::new(self)
+    }
+}
+
+impl RenderResource for SkyboxMaterial {
+    fn resource_type(&self) -> Option<RenderResourceType> {
+        Some(RenderResourceType::Buffer)
+    }
+
+    fn write_buffer_bytes(&self, buffer: &mut [u8]) {
+        let params = self.params();
+        let bytes = params[..].as_bytes();
+        buffer[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn buffer_byte_len(&self) -> Option<usize> {
+        Some(std::mem::size_of::<[Vec4; SKYBOX_MATERIAL_PARAMS_LEN]>())
+    }
+
+    fn texture(&self) -> Option<&Handle<Texture>> {
+        None
+    }
+}
+
 /// Resource to help with converting skyboxes stored as vertically stacked images as described in
 /// the [crate] documentation into array textures in the correct format for use in a
 /// [`SkyboxMaterial`].
@@ -342,19 +1065,128 @@ impl Default for SkyboxMaterial {
 /// actual texture conversion. Conversion is performed using
 /// [`Texture::reinterpret_stacked_2d_as_array`]. If you prefer, you are free to handle converting
 /// textures yourself, or use a texture format + loader which can load array textures directly.
+///
+/// Queued textures are kept loaded until they've been converted, even if the handles passed in are
+/// weak, as long as they're queued in a startup system or before [`SkyboxSystem::Convert`], so a
+/// texture loaded only for a skybox can be queued without keeping a handle to it elsewhere.
+/// Textures passed to [`keep_converting`](Self::keep_converting) are kept loaded for as long as
+/// they're tracked, and [`cancel`](Self::cancel) releases them.
+///
+/// Any texture used by a [`SkyboxMaterial`] which is still a single `N` by `6*N` image, or `N` by
+/// `6*N*skies` for several skies, is also converted automatically once both have loaded, so
+/// forgetting to queue a texture here doesn't leave the sky blank.
+///
+/// Textures built by the crate itself, such as with [`SkyboxTextureBuilder`], [`StarCubemap`],
+/// [`CloudCubemaps`], [`generate_gradient_cubemap`], or [`equirectangular_to_skybox`], are already
+/// 6-layer array textures, so they don't need to go through this.
 #[derive(Default)]
 pub struct SkyboxTextureConversion {
-    /// List of texture handles that should be skyboxes.
-    handles: Vec<Handle<Texture>>,
+    /// List of texture handles that should be skyboxes, with the number of skies in each.
+    handles: Vec<(Handle<Texture>, u32)>,
+    /// Textures which are converted again whenever they change.
+    tracked: Vec<Handle<Texture>>,
+    /// Separate face images waiting to load, with the texture to stitch them into.
+    assemblies: Vec<([Handle<Texture>; 6], Handle<Texture>)>,
+    /// Textures which have been converted and haven't been removed since.
+    converted: HashSet<HandleId>,
 }
 
 impl SkyboxTextureConversion {
     /// Takes a handle to a texture whose dimensions are `N` wide by `6*N` high, waits for it to load,
     /// and then reinterprets that texture as an array of 6 textures suitable or a skybox. This is
-    /// useful if your skybox texture is not in a format that has layers. This should only be done
-    /// once per testure, and will panic if the texture has already be reinterpreted.
+    /// useful if your skybox texture is not in a format that has layers. Textures used by a
+    /// [`SkyboxMaterial`] are converted automatically, so this is only needed to convert a texture
+    /// before it's added to a material. Textures which have already been reinterpreted are left as
+    /// they are.
     pub fn make_array(&mut self, handle: Handle<Texture>) {
-        self.handles.push(handle);
+        self.make_multi_sky_array(handle, 1);
+    }
+
+    /// Like [`make_array`](Self::make_array), but for a texture `N` wide by `6*N*skies` high which
+    /// holds several skies stacked on top of each other. The resulting texture has 6 layers per
+    /// sky, and the sky to show is picked with the [`SkyboxMaterial`]'s `layer`.
+    pub fn make_multi_sky_array(&mut self, handle: Handle<Texture>, skies: u32) {
+        self.handles.push((handle, skies));
+    }
+
+    /// Like [`make_array`](Self::make_array), but keeps converting the texture every time it is
+    /// replaced or modified while it is still an `N` by `6*N` image. This is meant for skies which
+    /// are generated at runtime, for example by a procedural atmosphere, and re-rendered as time
+    /// passes. Textures which are already 6-layer arrays don't need this: changes to them are
+    /// uploaded and show up in any [`SkyboxMaterial`] using them without any conversion.
+    pub fn keep_converting(&mut self, handle: Handle<Texture>) {
+        self.tracked.push(handle);
+    }
+
+    /// Waits for six separate face images to load, such as six PNG files, then stitches them into a
+    /// skybox texture. The faces are in the same order as the texture's layers: right (+X), left
+    /// (-X), top (+Y), bottom (-Y), back (+Z), and front (-Z), oriented as in the crate docs. They
+    /// must all be square, the same size, and in the same `Rgba8UnormSrgb` or `Rgba8Unorm` format;
+    /// if they aren't, a warning is logged and the texture is never filled in.
+    ///
+    /// Returns a handle to the stitched texture, which can be used in a [`SkyboxMaterial`] straight
+    /// away, as [`SkyboxMaterial::from_faces`] does. Skyboxes using it aren't drawn until it's
+    /// ready. The face images aren't needed afterwards, and are unloaded once nothing else uses
+    /// them.
+    pub fn assemble_faces(
+        &mut self,
+        faces: [Handle<Texture>; 6],
+        textures: &Assets<Texture>,
+    ) -> Handle<Texture> {
+        let texture = textures.get_handle(HandleId::random::<Texture>());
+        self.assemblies.push((faces, texture.clone()));
+        texture
+    }
+
+    /// Number of textures passed to [`make_array`](Self::make_array),
+    /// [`make_multi_sky_array`](Self::make_multi_sky_array), or
+    /// [`assemble_faces`](Self::assemble_faces) which are still waiting to load and be converted.
+    /// Useful for showing progress on a loading screen.
+    ///
+    /// ```no_run
+    /// # use bevy::prelude::*;
+    /// # use bevy_skybox_cubemap::SkyboxTextureConversion;
+    /// fn loading_screen(conversion: Res<SkyboxTextureConversion>) {
+    ///     if conversion.pending() > 0 {
+    ///         info!("Waiting on {} skies", conversion.pending());
+    ///     }
+    /// }
+    /// ```
+    pub fn pending(&self) -> usize {
+        self.handles.len() + self.assemblies.len()
+    }
+
+    /// Stops converting `handle`, whether it's still waiting to load or was passed to
+    /// [`keep_converting`](Self::keep_converting), so a texture from an aborted level load isn't
+    /// reinterpreted later after it's been reused for something else. Textures which were already
+    /// converted stay converted. Returns whether there was anything to cancel.
+    pub fn cancel(&mut self, handle: &Handle<Texture>) -> bool {
+        let queued = self.handles.len() + self.tracked.len() + self.assemblies.len();
+        self.handles.retain(|(pending, _)| pending != handle);
+        self.tracked.retain(|tracked| tracked != handle);
+        self.assemblies.retain(|(_, texture)| texture != handle);
+        queued != self.handles.len() + self.tracked.len() + self.assemblies.len()
+    }
+
+    /// Cancels every conversion, like calling [`cancel`](Self::cancel) on each of them.
+    pub fn clear(&mut self) {
+        self.handles.clear();
+        self.tracked.clear();
+        self.assemblies.clear();
+    }
+
+    /// Whether `handle` is still waiting to load and be converted.
+    pub fn is_pending(&self, handle: &Handle<Texture>) -> bool {
+        self.handles.iter().any(|(pending, _)| pending == handle)
+            || self.assemblies.iter().any(|(_, texture)| texture == handle)
+    }
+
+    /// Whether `handle` has been converted into an array texture and is ready to use in a
+    /// [`SkyboxMaterial`]. Textures passed to [`keep_converting`](Self::keep_converting) count once
+    /// they've been converted the first time. Textures which were already array textures, and so
+    /// never needed converting, don't count.
+    pub fn is_converted(&self, handle: &Handle<Texture>) -> bool {
+        self.converted.contains(&handle.id)
     }
 }
 
@@ -363,37 +1195,198 @@ impl SkyboxTextureConversion {
 fn convert_skyboxes(
     mut conversions: ResMut<SkyboxTextureConversion>,
     mut textures: ResMut<Assets<Texture>>,
+    materials: Res<Assets<SkyboxMaterial>>,
+    mut events: EventReader<AssetEvent<Texture>>,
+    mut material_events: EventReader<AssetEvent<SkyboxMaterial>>,
 ) {
+    // Queued textures are kept loaded until they're converted, even if only weak handles to them
+    // were queued. Upgrading them here, in the frame they were queued, gets in before Bevy frees
+    // assets without any strong handles left at the start of the next frame.
+    let weak = conversions
+        .handles
+        .iter()
+        .any(|(handle, _)| handle.is_weak())
+        || conversions.tracked.iter().any(Handle::is_weak)
+        || conversions
+            .assemblies
+            .iter()
+            .any(|(faces, _)| faces.iter().any(Handle::is_weak));
+    if weak {
+        let conversions = &mut *conversions;
+        let handles = conversions.handles.iter_mut().map(|(handle, _)| handle);
+        let faces = conversions
+            .assemblies
+            .iter_mut()
+            .flat_map(|(faces, _)| faces.iter_mut());
+        for handle in handles.chain(conversions.tracked.iter_mut()).chain(faces) {
+            handle.make_strong(&mut textures);
+        }
+    }
+
+    // Stacked textures used by a material are converted automatically, both when the material is
+    // added or changed and when the texture finishes loading.
+    let mut used = Vec::new();
+    let mut loaded = Vec::new();
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { handle } => {
+                conversions.converted.remove(&handle.id);
+                continue;
+            }
+        };
+        if !conversions.tracked.contains(handle) {
+            loaded.push(handle.id);
+            continue;
+        }
+        // Converting the texture modifies it again, so only convert it while it's still stacked.
+        let stacked = matches!(
+            textures.get(handle),
+            Some(texture) if texture.size.depth == 1 && texture.size.height == 6 * texture.size.width
+        );
+        if stacked {
+            if let Some(texture) = textures.get_mut(handle) {
+                texture.reinterpret_stacked_2d_as_array(6);
+                conversions.converted.insert(handle.id);
+            }
+        }
+    }
+    for event in material_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if let Some(material) = materials.get(handle) {
+                used.extend(material_textures(material).map(|texture| texture.id));
+            }
+        }
+    }
+    if !loaded.is_empty() {
+        for (_, material) in materials.iter() {
+            used.extend(
+                material_textures(material)
+                    .map(|texture| texture.id)
+                    .filter(|texture| loaded.contains(texture)),
+            );
+        }
+    }
+
     let mut i = 0;
-    loop {
-        // Check each texture in the pending queue to see if it is loaded yet.
-        let (handle, texture) = match conversions.handles.get(i) {
-            Some(handle) => match textures.get_mut(handle) {
-                // If it's loaded, take it out of the queue.
-                Some(texture) => (conversions.handles.remove(i), texture),
-                None => {
-                    i += 1;
-                    continue;
-                }
-            },
-            None => break,
+    // Check each texture in the pending queue to see if it is loaded yet.
+    while let Some((handle, _)) = conversions.handles.get(i) {
+        let ((handle, skies), texture) = match textures.get_mut(handle) {
+            // If it's loaded, take it out of the queue.
+            Some(texture) => (conversions.handles.remove(i), texture),
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+        // A material using the texture may already have had it converted automatically.
+        if texture.size.depth == 1 {
+            debug!(
+                "Reinterpreting as Skybox Texture {:?}: format: {:?}, len: {}, extents: {:?}",
+                handle,
+                texture.format,
+                texture.data.len(),
+                texture.size
+            );
+            texture.reinterpret_stacked_2d_as_array(6 * skies);
+        }
+        conversions.converted.insert(handle.id);
+    }
+
+    for id in used {
+        let skies = match textures.get(id) {
+            Some(texture)
+                if texture.dimension == TextureDimension::D2
+                    && texture.size.depth == 1
+                    && texture.size.width > 0
+                    && texture.size.height % (6 * texture.size.width) == 0 =>
+            {
+                texture.size.height / (6 * texture.size.width)
+            }
+            _ => continue,
         };
+        if let Some(texture) = textures.get_mut(id) {
+            debug!("Automatically reinterpreting {:?} as a skybox texture", id);
+            texture.reinterpret_stacked_2d_as_array(6 * skies);
+            conversions.converted.insert(id);
+        }
+    }
 
-        debug!(
-            "Reinterpreting as Skybox Texture {:?}: format: {:?}, len: {}, extents: {:?}",
-            handle,
-            texture.format,
-            texture.data.len(),
-            texture.size
-        );
-        texture.reinterpret_stacked_2d_as_array(6);
+    let mut i = 0;
+    while let Some((faces, _)) = conversions.assemblies.get(i) {
+        if !faces.iter().all(|face| textures.get(face).is_some()) {
+            i += 1;
+            continue;
+        }
+        let (faces, texture) = conversions.assemblies.remove(i);
+        match stitch_faces(faces.iter().map(|face| textures.get(face).unwrap())) {
+            Some(stitched) => {
+                // Materials using the texture hold their own strong handles to it.
+                let _ = textures.set(&texture, stitched);
+                conversions.converted.insert(texture.id);
+            }
+            None => warn!(
+                "Skybox faces {:?} aren't square RGBA8 images of the same size and format",
+                faces.iter().map(|face| face.id).collect::<Vec<_>>()
+            ),
+        }
     }
 }
 
+/// Textures a material samples from.
+fn material_textures(material: &SkyboxMaterial) -> impl Iterator<Item = &Handle<Texture>> {
+    std::iter::once(&material.texture)
+        .chain(std::iter::once(&material.blend_texture))
+        .chain(std::iter::once(&material.blend_texture_2))
+        .chain(std::iter::once(&material.blend_texture_3))
+        .chain(std::iter::once(&material.lower_texture))
+        .flatten()
+}
+
+/// Stitches six face images into a 6-layer array texture, or returns `None` if they don't match.
+fn stitch_faces<'a>(faces: impl Iterator<Item = &'a Texture>) -> Option<Texture> {
+    let mut stitched: Option<Texture> = None;
+    for face in faces {
+        let size = face.size.width;
+        let len = (size * size) as usize * 4;
+        let valid = size > 0
+            && face.size.height == size
+            && face.size.depth == 1
+            && matches!(
+                face.format,
+                TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+            )
+            && face.data.len() == len;
+        if !valid {
+            return None;
+        }
+        match &mut stitched {
+            Some(stitched) => {
+                if stitched.size.width != size || stitched.format != face.format {
+                    return None;
+                }
+                stitched.data.extend_from_slice(&face.data);
+                stitched.size.depth += 1;
+            }
+            None => {
+                stitched = Some(Texture::new(
+                    face.size,
+                    face.dimension,
+                    face.data.clone(),
+                    face.format,
+                ))
+            }
+        }
+    }
+    stitched.filter(|stitched| stitched.size.depth == 6)
+}
+
 /// Constants defining node names in the render graph.
 pub mod node {
     /// Node for the `SkyboxMaterial`.
     pub const SKYBOX_MATERIAL: &str = "skybox_material";
+    /// Node for each camera's `SkyboxCameraTint`.
+    pub const SKYBOX_CAMERA_TINT: &str = "skybox_camera_tint";
 }
 
 /// Add the render graph and pipeline for the skybox to the world.
@@ -404,9 +1397,11 @@ fn add_skybox_graph(world: &mut World) {
             node::SKYBOX_MATERIAL,
             AssetRenderResourcesNode::<SkyboxMaterial>::new(true),
         );
-        graph
-            .add_node_edge(node::SKYBOX_MATERIAL, base::node::MAIN_PASS)
-            .unwrap();
+        graph.add_system_node(
+            node::SKYBOX_CAMERA_TINT,
+            camera::SkyboxCameraTintNode::default(),
+        );
+        add_skybox_pass(&mut graph, base::node::MAIN_PASS).unwrap();
     }
 
     let pipeline = build_skybox_pipeline(&mut world.get_resource_mut::<Assets<Shader>>().unwrap());
@@ -416,20 +1411,261 @@ fn add_skybox_graph(world: &mut World) {
     pipelines.set_untracked(SKYBOX_PIPELINE_HANDLE, pipeline);
 }
 
+/// Sets up a pass node added to the render graph after the [`SkyboxPlugin`], such as one drawing
+/// to a second window, to draw skyboxes. The skybox's own nodes are ordered before `pass`, so the
+/// materials and the [`SkyboxCameraTint`] of each camera are ready before it draws. The plugin does
+/// this for Bevy's main pass, and the pass should draw the [`MainPass`] entities like it does.
+///
+/// The pass's cameras also need to be added to Bevy's `ActiveCameras`, which gives them the
+/// binding skyboxes are drawn with. Every window uses the same surface format in Bevy 0.5, so the
+/// skybox pipeline works in all of them, and different windows can show different skyboxes by
+/// giving their cameras and skyboxes different render layers. [`SkyboxZone`]s and
+/// [`SkyboxWaterLevel`]s still follow Bevy's main 3D camera. See the `multiple_windows` example for
+/// a complete setup.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::render::render_graph::RenderGraph;
+/// # use bevy_skybox_cubemap::add_skybox_pass;
+/// fn setup_second_window(mut render_graph: ResMut<RenderGraph>) {
+///     // Add the second window's swap chain, depth texture, camera, and pass nodes, then:
+///     add_skybox_pass(&mut render_graph, "second_window_pass").unwrap();
+/// }
+/// ```
+pub fn add_skybox_pass(
+    graph: &mut RenderGraph,
+    pass: impl Into<NodeLabel>,
+) -> Result<(), RenderGraphError> {
+    let pass = pass.into();
+    graph.add_node_edge(node::SKYBOX_MATERIAL, &pass)?;
+    graph.add_node_edge(node::SKYBOX_CAMERA_TINT, &pass)
+}
+
 /// Handle to use to reference the skybox pipeline.
 pub const SKYBOX_PIPELINE_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(PipelineDescriptor::TYPE_UUID, 16037920303847147810);
 
+/// Adds a copy of the skybox pipeline which uses a different primitive state, for example to change
+/// the cull mode or winding order. Use the returned handle with [`SkyboxBundle::with_pipeline`].
+///
+/// The default pipeline culls front faces with counter-clockwise winding, which draws the inside of
+/// the [`SKYBOX_MESH_HANDLE`] cube. Custom meshes such as inverted spheres or domes, or passes with
+/// a mirrored view, may need back faces culled or no culling at all instead.
+///
+/// Must be called after [`SkyboxPlugin`] has been added.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::render::pipeline::{CullMode, PipelineDescriptor};
+/// # use bevy_skybox_cubemap::{skybox_pipeline_with_primitive, SkyboxBundle, SkyboxMaterial};
+/// fn setup(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     // A dome modeled with its faces pointing inwards.
+///     let dome: Handle<Mesh> = asset_server.load("sky_dome.gltf#Mesh0/Primitive0");
+///     let pipeline = skybox_pipeline_with_primitive(&mut pipelines, |primitive| {
+///         primitive.cull_mode = CullMode::Back;
+///     });
+///     commands.spawn_bundle(SkyboxBundle {
+///         mesh: dome,
+///         ..SkyboxBundle::new(skyboxes.add(SkyboxMaterial::from_color(Color::ALICE_BLUE)))
+///             .with_pipeline(pipeline)
+///     });
+/// }
+/// ```
+pub fn skybox_pipeline_with_primitive(
+    pipelines: &mut Assets<PipelineDescriptor>,
+    configure: impl FnOnce(&mut PrimitiveState),
+) -> Handle<PipelineDescriptor> {
+    add_skybox_pipeline_variant(pipelines, |pipeline| configure(&mut pipeline.primitive))
+}
+
+/// Adds a copy of the skybox pipeline which combines the skybox's color with what's behind it
+/// using `mode`. Use the returned handle with [`SkyboxBundle::with_pipeline`].
+///
+/// This makes it possible to layer several skyboxes, such as stars added over a gradient. Bevy
+/// draws opaque entities before transparent ones, so mark each layer above the bottom one as
+/// transparent with [`Visible::is_transparent`]. Transparent entities are drawn furthest first,
+/// and since the skybox shader ignores translation, the layers can be ordered by moving the ones
+/// which should be drawn first further from the camera.
+///
+/// Must be called after [`SkyboxPlugin`] has been added.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::render::pipeline::PipelineDescriptor;
+/// # use bevy_skybox_cubemap::{
+/// #     skybox_pipeline_with_blend_mode, SkyboxBlendMode, SkyboxBundle, SkyboxMaterial,
+/// # };
+/// fn setup(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     // Both textures are already 6-layer arrays, like those baked by `StarCubemap`.
+///     let gradient = asset_server.load("gradient_skybox.dds");
+///     let stars = asset_server.load("stars_skybox.dds");
+///     let gradient = skyboxes.add(SkyboxMaterial::from_texture(gradient));
+///     let stars = skyboxes.add(SkyboxMaterial::from_texture(stars));
+///     commands.spawn_bundle(SkyboxBundle::new(gradient));
+///     let additive = skybox_pipeline_with_blend_mode(&mut pipelines, SkyboxBlendMode::Additive);
+///     commands.spawn_bundle(SkyboxBundle {
+///         visible: Visible {
+///             is_visible: true,
+///             is_transparent: true,
+///         },
+///         ..SkyboxBundle::new(stars).with_pipeline(additive)
+///     });
+/// }
+/// ```
+pub fn skybox_pipeline_with_blend_mode(
+    pipelines: &mut Assets<PipelineDescriptor>,
+    mode: SkyboxBlendMode,
+) -> Handle<PipelineDescriptor> {
+    add_skybox_pipeline_variant(pipelines, |pipeline| {
+        for target in &mut pipeline.color_target_states {
+            target.color_blend = mode.color_blend();
+        }
+    })
+}
+
+/// Adds a copy of the skybox pipeline with any part of its descriptor changed by `configure`, for
+/// drawing skyboxes in custom render graphs. Use the returned handle with
+/// [`SkyboxBundle::with_pipeline`].
+///
+/// Bevy specializes pipelines for each pass's sample count, but not for its attachment formats, so
+/// passes drawing to anything but a window need a copy with their color and depth formats. The
+/// default pipeline draws to `TextureFormat::default()` with a `Depth32Float` depth buffer.
+/// Bevy builds the bind group layout from the shaders, which use:
+///
+/// - set 0: the camera's `CameraViewProj` and `CameraView` at bindings 0 and 1, and its
+///   `SkyboxCamera` uniform at binding 2, which the plugin gives every active camera;
+/// - set 1: the entity's `Transform` at binding 0;
+/// - set 2: the [`SkyboxMaterial`]'s bindings, which are kept up to date by the node named
+///   [`node::SKYBOX_MATERIAL`].
+///
+/// A `PassNode` draws the entities with the component it's created for, so to draw skyboxes in a
+/// pass of your own, swap the [`MainPass`] of their [`SkyboxBundle`] for your pass's component,
+/// and set the pass up with [`add_skybox_pass`].
+///
+/// Must be called after [`SkyboxPlugin`] has been added.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::render::pipeline::PipelineDescriptor;
+/// # use bevy::render::render_graph::base::MainPass;
+/// # use bevy::render::texture::TextureFormat;
+/// # use bevy_skybox_cubemap::{skybox_pipeline_with_descriptor, SkyboxBundle, SkyboxMaterial};
+/// // Marker for the entities drawn by a pass rendering to an HDR texture.
+/// struct HdrPass;
+///
+/// fn setup(
+///     mut commands: Commands,
+///     mut pipelines: ResMut<Assets<PipelineDescriptor>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     let pipeline = skybox_pipeline_with_descriptor(&mut pipelines, |pipeline| {
+///         pipeline.color_target_states[0].format = TextureFormat::Rgba16Float;
+///     });
+///     commands
+///         .spawn_bundle(
+///             SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())).with_pipeline(pipeline),
+///         )
+///         .remove::<MainPass>()
+///         .insert(HdrPass);
+/// }
+/// ```
+pub fn skybox_pipeline_with_descriptor(
+    pipelines: &mut Assets<PipelineDescriptor>,
+    configure: impl FnOnce(&mut PipelineDescriptor),
+) -> Handle<PipelineDescriptor> {
+    add_skybox_pipeline_variant(pipelines, configure)
+}
+
+/// Adds a copy of the skybox pipeline changed by `configure`.
+fn add_skybox_pipeline_variant(
+    pipelines: &mut Assets<PipelineDescriptor>,
+    configure: impl FnOnce(&mut PipelineDescriptor),
+) -> Handle<PipelineDescriptor> {
+    let mut pipeline = pipelines
+        .get(SKYBOX_PIPELINE_HANDLE)
+        .expect("SkyboxPlugin must be added before creating skybox pipelines")
+        .clone();
+    configure(&mut pipeline);
+    pipelines.add(pipeline)
+}
+
+/// How a skybox's color is combined with whatever has already been drawn behind it. See
+/// [`skybox_pipeline_with_blend_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SkyboxBlendMode {
+    /// Replaces whatever is behind the skybox, ignoring its alpha.
+    Opaque,
+    /// Blends over whatever is behind the skybox using its alpha. This is what the default
+    /// skybox pipeline does.
+    Alpha,
+    /// Adds the skybox's color, scaled by its alpha, to whatever is behind it. Useful for stars and
+    /// glows.
+    Additive,
+    /// Blends over whatever is behind the skybox, treating its color as already multiplied by its
+    /// alpha.
+    Premultiplied,
+}
+
+impl SkyboxBlendMode {
+    /// Color blend state for this mode.
+    fn color_blend(self) -> BlendState {
+        let (src_factor, dst_factor) = match self {
+            SkyboxBlendMode::Opaque => (BlendFactor::One, BlendFactor::Zero),
+            SkyboxBlendMode::Alpha => (BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+            SkyboxBlendMode::Additive => (BlendFactor::SrcAlpha, BlendFactor::One),
+            SkyboxBlendMode::Premultiplied => (BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        };
+        BlendState {
+            src_factor,
+            dst_factor,
+            operation: BlendOperation::Add,
+        }
+    }
+}
+
 fn add_skybox_mesh(meshes: &mut Assets<Mesh>) {
     // Skybox mesh needs to be large enough not to get caught in the camera's near-clip plane (but
     // can otherwise be any value).
     meshes.set_untracked(SKYBOX_MESH_HANDLE, Mesh::from(shape::Cube { size: 1.0 }));
+    meshes.set_untracked(SKYBOX_DOME_MESH_HANDLE, mesh::dome(32, 8));
+    meshes.set_untracked(
+        SKYBOX_SPHERE_MESH_HANDLE,
+        Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 8,
+        }),
+    );
 }
 
 /// Handle to use to reference the skybox mesh.
 pub const SKYBOX_MESH_HANDLE: HandleUntyped =
     HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 7423141153313829192);
 
+/// Handle to a skydome mesh which can be used in place of [`SKYBOX_MESH_HANDLE`]: a hemisphere
+/// above the horizon, closed off below by a shallow cone for the ground. Pair it with the
+/// [`SkyboxMaterial`]'s `ground_color` so the sky fades into the ground below the horizon, for
+/// games where the bottom of the sky texture is never seen.
+pub const SKYBOX_DOME_MESH_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 13580531731236323145);
+
+/// Handle to a subdivided icosphere mesh which can be used in place of [`SKYBOX_MESH_HANDLE`].
+/// The skybox looks the same either way, but the sphere's many evenly spread vertices suit custom
+/// pipelines which compute fog, gradients, or animation per vertex, which look blocky on the 8
+/// corners of the cube.
+pub const SKYBOX_SPHERE_MESH_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 2871647338545325977);
+
 /// Build the render pipeline for the skybox vertex and fragment shaders.
 fn build_skybox_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
     PipelineDescriptor {
@@ -454,11 +1690,7 @@ fn build_skybox_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
         }),
         color_target_states: vec![ColorTargetState {
             format: TextureFormat::default(),
-            color_blend: BlendState {
-                src_factor: BlendFactor::SrcAlpha,
-                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                operation: BlendOperation::Add,
-            },
+            color_blend: SkyboxBlendMode::Alpha.color_blend(),
             alpha_blend: BlendState {
                 src_factor: BlendFactor::One,
                 dst_factor: BlendFactor::One,
@@ -0,0 +1,95 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light pollution glowing over the horizon of night skies.
+
+use bevy::prelude::*;
+
+/// Maximum number of cities a [`LightPollution`] glow can lean towards.
+pub const MAX_CITY_GLOWS: usize = 4;
+
+/// Settings for the glow of city lights over the horizon, set on
+/// [`SkyboxMaterial::light_pollution`](crate::SkyboxMaterial::light_pollution). Light scattered
+/// back down by the air over a city washes out the lower part of the night sky, so urban scenes
+/// can use the same star texture as rural ones. The glow is added on top of the sky, so it's
+/// usually only set at night, and it fades out upwards from the horizon.
+///
+/// With no `city_azimuths`, the glow is the same all around the horizon. Otherwise, it's
+/// strongest towards each city, by how much `directionality` gives, so a town on one side of the
+/// player lights up that side of the sky.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{LightPollution, SkyboxMaterial};
+/// # fn setup(mut skyboxes: ResMut<Assets<SkyboxMaterial>>, skybox: Handle<SkyboxMaterial>) {
+/// // A city to the east, and a smaller glow all around.
+/// skyboxes.get_mut(skybox).unwrap().light_pollution = Some(LightPollution {
+///     city_azimuths: vec![std::f32::consts::FRAC_PI_2],
+///     ..Default::default()
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LightPollution {
+    /// Color of the glow. Sodium street lights give an orange glow, and LEDs a whiter one.
+    pub color: Color,
+    /// Brightness of the glow at the horizon.
+    pub intensity: f32,
+    /// Angle in radians above the horizon over which the glow fades out.
+    pub height: f32,
+    /// Compass bearings of the cities the glow leans towards, in radians clockwise from north
+    /// (-Z) towards east (+X), like those in [`astronomy`](crate::astronomy). Only the first
+    /// [`MAX_CITY_GLOWS`] are used.
+    pub city_azimuths: Vec<f32>,
+    /// Angle in radians to either side of each city over which its glow fades out.
+    pub city_spread: f32,
+    /// How much of the glow comes from the cities rather than being spread evenly around the
+    /// horizon, from `0.0` to `1.0`. Has no effect without any `city_azimuths`.
+    pub directionality: f32,
+}
+
+impl Default for LightPollution {
+    fn default() -> Self {
+        Self {
+            color: Color::rgb(1.0, 0.6, 0.3),
+            intensity: 0.3,
+            height: 0.25,
+            city_azimuths: Vec::new(),
+            city_spread: 0.5,
+            directionality: 0.7,
+        }
+    }
+}
+
+impl LightPollution {
+    /// Packs the settings for the `SkyboxMaterial_params` uniform block. The order must match the
+    /// block in `skybox.frag`.
+    pub(crate) fn params(&self) -> [Vec4; 3] {
+        let color: Vec4 = self.color.as_linear_rgba_f32().into();
+        let cities = self.city_azimuths.len().min(MAX_CITY_GLOWS);
+        let mut azimuths = [0.0; MAX_CITY_GLOWS];
+        azimuths[..cities].copy_from_slice(&self.city_azimuths[..cities]);
+        [
+            (color.truncate() * self.intensity).extend(self.height),
+            Vec4::new(
+                self.directionality.clamp(0.0, 1.0),
+                self.city_spread,
+                cities as f32,
+                0.0,
+            ),
+            azimuths.into(),
+        ]
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lightning flashes for stormy skies.
+
+use bevy::prelude::*;
+
+use crate::random::Rng;
+use crate::SkyboxMaterial;
+
+/// Component which makes a skybox flash with lightning.
+///
+/// Flashes can happen randomly, on average every `mean_interval` seconds, or be triggered manually
+/// with [`SkyboxLightning::strike`]. Each flash brightens the sky around a direction for `duration`
+/// seconds with a few quick flickers. The [`SkyboxPlugin`](crate::SkyboxPlugin) drives the flashes
+/// through the `flash_*` fields of the skybox's [`SkyboxMaterial`], so all skyboxes sharing the
+/// material flash together.
+///
+/// Whenever a flash starts, a [`LightningFlash`] event is sent, which can be used to play thunder.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{LightningFlash, SkyboxLightning};
+/// # fn setup(mut commands: Commands, skybox: Entity) {
+/// // A flash every 8 seconds on average.
+/// commands.entity(skybox).insert(SkyboxLightning::random(8.0));
+/// # }
+///
+/// fn thunder(mut flashes: EventReader<LightningFlash>) {
+///     for flash in flashes.iter() {
+///         // Sound travels about 343 meters per second.
+///         let delay = flash.distance / 343.0;
+///         // Play thunder after `delay` seconds.
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SkyboxLightning {
+    /// Average time between random flashes, in seconds. If `None`, flashes only happen when
+    /// triggered with [`SkyboxLightning::strike`].
+    pub mean_interval: Option<f32>,
+    /// How long each flash lasts, in seconds.
+    pub duration: f32,
+    /// Peak brightness of a flash.
+    pub intensity: f32,
+    /// Angular radius of the brightened region of the sky, in radians.
+    pub spread: f32,
+    /// Color of the flashes.
+    pub color: Color,
+    /// Range of elevations, in radians above the horizon, for random flashes.
+    pub elevation: (f32, f32),
+    /// Range of distances, in meters, for random flashes. Only used to fill in
+    /// [`LightningFlash::distance`].
+    pub distance: (f32, f32),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rng: Rng,
+    /// Time until the next random flash.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    countdown: Option<f32>,
+    /// The flash currently being shown.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    active: Option<ActiveFlash>,
+    /// Flashes triggered with `strike` which haven't started yet.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending: Vec<(Vec3, f32)>,
+}
+
+impl SkyboxLightning {
+    /// Creates a `SkyboxLightning` with random flashes on average every `mean_interval` seconds.
+    pub fn random(mean_interval: f32) -> Self {
+        Self {
+            mean_interval: Some(mean_interval),
+            ..Default::default()
+        }
+    }
+
+    /// Reseeds the random number generator used for random flashes.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Triggers a flash in `direction`, `distance` meters away. If another flash is in progress,
+    /// this one will start after it.
+    pub fn strike(&mut self, direction: Vec3, distance: f32) {
+        self.pending.push((direction, distance));
+    }
+
+    /// Whether a flash is currently being shown.
+    pub fn is_flashing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Picks a direction and distance for a random flash.
+    fn random_strike(&mut self) -> (Vec3, f32) {
+        let direction = self.rng.direction(self.elevation);
+        let distance = self.rng.range(self.distance.0, self.distance.1);
+        (direction, distance)
+    }
+
+    /// Picks the time until the next random flash.
+    fn random_countdown(&mut self) -> Option<f32> {
+        let mean_interval = self.mean_interval?;
+        Some(self.rng.exponential(mean_interval))
+    }
+}
+
+impl Default for SkyboxLightning {
+    fn default() -> Self {
+        Self {
+            mean_interval: None,
+            duration: 0.6,
+            intensity: 2.0,
+            spread: 0.8,
+            color: Color::rgb(0.85, 0.9, 1.0),
+            elevation: (0.05, 0.6),
+            distance: (500.0, 5000.0),
+            rng: Rng::new(0x5eed),
+            countdown: None,
+            active: None,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Flash currently being shown.
+#[derive(Debug, Clone)]
+struct ActiveFlash {
+    direction: Vec3,
+    elapsed: f32,
+}
+
+/// Event sent when a lightning flash starts.
+#[derive(Debug, Clone)]
+pub struct LightningFlash {
+    /// Skybox entity the flash happened on.
+    pub skybox: Entity,
+    /// World-space direction of the flash.
+    pub direction: Vec3,
+    /// Distance to the lightning, in meters.
+    pub distance: f32,
+}
+
+/// Brightness of a flash, from `0.0` to `1.0`, at `progress` from `0.0` to `1.0` through it. Real
+/// lightning usually flickers as it strikes several times along the same channel, so this is a few
+/// quickly decaying pulses.
+fn flash_envelope(progress: f32) -> f32 {
+    const PULSES: [(f32, f32); 3] = [(0.0, 1.0), (0.2, 0.6), (0.4, 0.85)];
+    if !(0.0..1.0).contains(&progress) {
+        return 0.0;
+    }
+    let pulses: f32 = PULSES
+        .iter()
+        .filter(|(start, _)| progress >= *start)
+        .map(|(start, strength)| strength * (-(progress - start) * 12.0).exp())
+        .sum();
+    (pulses * (1.0 - progress)).min(1.0)
+}
+
+/// System which runs [`SkyboxLightning`] flashes.
+pub(crate) fn run_lightning(
+    time: Res<Time>,
+    mut flashes: EventWriter<LightningFlash>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut skyboxes: Query<(Entity, &mut SkyboxLightning, &Handle<SkyboxMaterial>)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut lightning, handle) in skyboxes.iter_mut() {
+        let lightning = &mut *lightning;
+        if let Some(active) = &mut lightning.active {
+            active.elapsed += delta;
+            if active.elapsed >= lightning.duration {
+                lightning.active = None;
+            }
+        }
+
+        if lightning.mean_interval.is_none() {
+            lightning.countdown = None;
+        } else if lightning.countdown.is_none() {
+            lightning.countdown = lightning.random_countdown();
+        }
+        if let Some(countdown) = &mut lightning.countdown {
+            *countdown -= delta;
+            if *countdown <= 0.0 {
+                let strike = lightning.random_strike();
+                lightning.pending.push(strike);
+                lightning.countdown = lightning.random_countdown();
+            }
+        }
+
+        if lightning.active.is_none() && !lightning.pending.is_empty() {
+            let (direction, distance) = lightning.pending.remove(0);
+            lightning.active = Some(ActiveFlash {
+                direction,
+                elapsed: 0.0,
+            });
+            flashes.send(LightningFlash {
+                skybox: entity,
+                direction,
+                distance,
+            });
+        }
+
+        let (direction, intensity) = match &lightning.active {
+            Some(active) => (
+                active.direction,
+                lightning.intensity * flash_envelope(active.elapsed / lightning.duration),
+            ),
+            None => (Vec3::Y, 0.0),
+        };
+        // Avoid re-uploading the material every frame while there's no flash.
+        match materials.get(handle) {
+            Some(material) if intensity != 0.0 || material.flash_intensity != 0.0 => {}
+            _ => continue,
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.flash_direction = direction;
+            material.flash_spread = lightning.spread;
+            material.flash_intensity = intensity;
+            material.flash_color = lightning.color;
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measuring how bright skyboxes are, and lighting scenes to match.
+
+use bevy::pbr::AmbientLight;
+use bevy::prelude::*;
+use bevy::render::texture::TextureFormat;
+use bevy::utils::HashMap;
+
+use crate::cube::{decode_table, FACES};
+use crate::SkyboxMaterial;
+
+/// Finds the average brightness of sky `layer` of a skybox texture, as the relative luminance of
+/// its linear colors: `0.0` for black and `1.0` for white. Each pixel is weighted by how much of the
+/// sky it covers, since pixels near the corners of each face cover less than those in the middle.
+///
+/// The texture can be either a 6-layer array texture or a stacked `N` by `6*N` image, and must be
+/// in `Rgba8UnormSrgb` or `Rgba8Unorm` format. Returns `None` if it isn't, or if it doesn't hold
+/// that many skies.
+pub fn average_sky_luminance(texture: &Texture, layer: u32) -> Option<f32> {
+    average_linear_color(texture, layer).map(linear_luminance)
+}
+
+/// Finds the average linear color of sky `layer` of a skybox texture, weighted like
+/// [`average_sky_luminance`], which explains what textures it works with.
+pub fn average_sky_color(texture: &Texture, layer: u32) -> Option<Color> {
+    average_linear_color(texture, layer).map(|color| Color::rgb_linear(color.x, color.y, color.z))
+}
+
+/// Average linear RGB of sky `layer` of a skybox texture.
+fn average_linear_color(texture: &Texture, layer: u32) -> Option<Vec3> {
+    let srgb = match texture.format {
+        TextureFormat::Rgba8UnormSrgb => true,
+        TextureFormat::Rgba8Unorm => false,
+        _ => return None,
+    };
+    let size = texture.size.width as usize;
+    if size == 0 {
+        return None;
+    }
+    let face_len = size * size * 4;
+    let start = face_len * FACES * layer as usize;
+    let sky = texture.data.get(start..start + face_len * FACES)?;
+
+    let decode = decode_table(srgb);
+    // Every face has the same weights, so work them out once.
+    let mut weights = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            weights.push((1.0 + u * u + v * v).powf(-1.5));
+        }
+    }
+    let total_weight: f32 = weights.iter().sum::<f32>() * FACES as f32;
+
+    let mut total = Vec3::ZERO;
+    for face in sky.chunks_exact(face_len) {
+        for (pixel, weight) in face.chunks_exact(4).zip(&weights) {
+            let color = Vec3::new(
+                decode[pixel[0] as usize],
+                decode[pixel[1] as usize],
+                decode[pixel[2] as usize],
+            );
+            total += color * *weight;
+        }
+    }
+    Some(total / total_weight)
+}
+
+/// Component which the [`SkyboxPlugin`](crate::SkyboxPlugin) keeps updated with how bright a
+/// skybox is, for auto-exposure and eye-adaptation systems to read, so that walking into an area
+/// with a dark sky lets the eye adjust to it.
+///
+/// `average` is the luminance of the sky as its material draws it, from
+/// [`average_sky_luminance`] of each of its textures, blended the way the material blends them and
+/// multiplied by its `color`, then faded towards its `fade_color`. Effects drawn over the sky, such
+/// as the sun, lightning, haze, and the ground, aren't included. Only skybox entities with this
+/// component are measured. It's updated in `CoreStage::PostUpdate`, and stays at its last value
+/// while a texture is still loading.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxLuminance, SkyboxMaterial};
+/// # fn setup(mut commands: Commands, mut skyboxes: ResMut<Assets<SkyboxMaterial>>) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())))
+///     .insert(SkyboxLuminance::default());
+/// # }
+///
+/// fn adapt_exposure(skyboxes: Query<&SkyboxLuminance>) {
+///     for luminance in skyboxes.iter() {
+///         info!("The sky's average luminance is {}", luminance.average);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SkyboxLuminance {
+    /// Average relative luminance of the sky, where `1.0` is plain white.
+    pub average: f32,
+}
+
+/// Component which has the [`SkyboxPlugin`](crate::SkyboxPlugin) keep Bevy's [`AmbientLight`]
+/// matched to a skybox, so indirect lighting follows the sky through a day-night cycle or a change
+/// of weather without being set by hand.
+///
+/// The ambient light's color is set to the average color of the sky as its material draws it,
+/// measured the same way as for [`SkyboxLuminance`], and its brightness to `brightness`. Whenever
+/// the material's textures, `color`, blend factor or weights, or fade change, the light eases
+/// towards the new color, getting most of the way there in `smoothing` seconds, so a sky being
+/// blended or switched doesn't make the lighting flicker. Only one skybox should have this
+/// component, since there's only one ambient light.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxAmbientLight, SkyboxBundle, SkyboxMaterial};
+/// # fn setup(mut commands: Commands, mut skyboxes: ResMut<Assets<SkyboxMaterial>>) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())))
+///     .insert(SkyboxAmbientLight::new(0.1));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxAmbientLight {
+    /// Brightness to give the ambient light.
+    pub brightness: f32,
+    /// How long, in seconds, the ambient light takes to get most of the way to a new sky color.
+    /// `0.0` changes it straight away.
+    pub smoothing: f32,
+}
+
+impl SkyboxAmbientLight {
+    /// Creates a `SkyboxAmbientLight` with the given `brightness`, which follows the sky straight
+    /// away.
+    pub fn new(brightness: f32) -> Self {
+        Self {
+            brightness,
+            smoothing: 0.0,
+        }
+    }
+
+    /// Eases the ambient light to new sky colors over about `smoothing` seconds.
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
+impl Default for SkyboxAmbientLight {
+    /// Bevy's default ambient brightness.
+    fn default() -> Self {
+        Self::new(AmbientLight::default().brightness)
+    }
+}
+
+/// Average linear colors of skybox texture skies, or `None` for those that can't be measured.
+type MeasuredSkies = HashMap<(Handle<Texture>, u32), Option<Vec3>>;
+
+/// System which measures skyboxes with a [`SkyboxLuminance`], and updates the [`AmbientLight`] for
+/// those with a [`SkyboxAmbientLight`].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn measure_skyboxes(
+    mut measured: Local<MeasuredSkies>,
+    mut texture_events: EventReader<AssetEvent<Texture>>,
+    time: Res<Time>,
+    textures: Res<Assets<Texture>>,
+    materials: Res<Assets<SkyboxMaterial>>,
+    ambient_light: Option<ResMut<AmbientLight>>,
+    mut skyboxes: Query<(&Handle<SkyboxMaterial>, &mut SkyboxLuminance)>,
+    ambient_skyboxes: Query<(&Handle<SkyboxMaterial>, &SkyboxAmbientLight)>,
+) {
+    for event in texture_events.iter() {
+        if let AssetEvent::Modified { handle } | AssetEvent::Removed { handle } = event {
+            measured.retain(|(measured, _), _| measured != handle);
+        }
+    }
+
+    for (handle, mut luminance) in skyboxes.iter_mut() {
+        let material = match materials.get(handle) {
+            Some(material) => material,
+            None => continue,
+        };
+        let sky = match sky_color(material, |texture| {
+            measure(&mut measured, &textures, texture, material.layer)
+        }) {
+            Some(sky) => sky,
+            None => continue,
+        };
+        let fade = material.fade_color.a().clamp(0.0, 1.0);
+        let average = linear_luminance(sky) * color_luminance(material.color) * (1.0 - fade)
+            + color_luminance(material.fade_color) * fade;
+        if luminance.average != average {
+            luminance.average = average;
+        }
+    }
+
+    let mut ambient_light = match ambient_light {
+        Some(ambient_light) => ambient_light,
+        None => return,
+    };
+    for (handle, settings) in ambient_skyboxes.iter() {
+        let material = match materials.get(handle) {
+            Some(material) => material,
+            None => continue,
+        };
+        let sky = match sky_color(material, |texture| {
+            measure(&mut measured, &textures, texture, material.layer)
+        }) {
+            Some(sky) => sky,
+            None => continue,
+        };
+        let linear = |color: Color| Vec4::from(color.as_linear_rgba_f32()).truncate();
+        let fade = material.fade_color.a().clamp(0.0, 1.0);
+        let target = (sky * linear(material.color)).lerp(linear(material.fade_color), fade);
+        let current = linear(ambient_light.color);
+        let color = if settings.smoothing > 0.0 {
+            current.lerp(
+                target,
+                1.0 - (-time.delta_seconds() / settings.smoothing).exp(),
+            )
+        } else {
+            target
+        };
+        if color != current || ambient_light.brightness != settings.brightness {
+            ambient_light.color = Color::rgb_linear(color.x, color.y, color.z);
+            ambient_light.brightness = settings.brightness;
+        }
+    }
+}
+
+/// Average linear color of sky `layer` of `texture`, measuring it if it hasn't been already.
+/// Missing textures count as plain white, like they do in the shader.
+fn measure(
+    measured: &mut MeasuredSkies,
+    textures: &Assets<Texture>,
+    texture: &Option<Handle<Texture>>,
+    layer: u32,
+) -> Option<Vec3> {
+    let texture = match texture {
+        Some(texture) => texture,
+        None => return Some(Vec3::ONE),
+    };
+    let key = (texture.clone_weak(), layer);
+    if let Some(&color) = measured.get(&key) {
+        return color;
+    }
+    let color = average_linear_color(textures.get(texture)?, layer);
+    measured.insert(key, color);
+    color
+}
+
+/// Linear color of a material's textures blended the way the shader blends them, or `None` if any
+/// of them can't be measured yet.
+fn sky_color(
+    material: &SkyboxMaterial,
+    mut texture_color: impl FnMut(&Option<Handle<Texture>>) -> Option<Vec3>,
+) -> Option<Vec3> {
+    let base = texture_color(&material.texture)?;
+    if let Some(weights) = material.blend_weights {
+        let weights = weights.max(Vec4::ZERO);
+        let total_weight = weights.dot(Vec4::ONE);
+        if total_weight <= 0.0 {
+            return Some(base);
+        }
+        let blended = base * weights.x
+            + texture_color(&material.blend_texture)? * weights.y
+            + texture_color(&material.blend_texture_2)? * weights.z
+            + texture_color(&material.blend_texture_3)? * weights.w;
+        Some(blended / total_weight)
+    } else if material.blend_texture.is_some() {
+        let blended = texture_color(&material.blend_texture)?;
+        Some(base.lerp(blended, material.blend_factor.clamp(0.0, 1.0)))
+    } else {
+        Some(base)
+    }
+}
+
+/// Relative luminance of a color's linear RGB.
+fn color_luminance(color: Color) -> f32 {
+    linear_luminance(Vec4::from(color.as_linear_rgba_f32()).truncate())
+}
+
+/// Relative luminance of linear RGB.
+fn linear_luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
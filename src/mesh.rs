@@ -0,0 +1,126 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alternative skybox meshes, and checks for custom ones.
+
+use std::collections::HashSet;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::asset::HandleId;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::render::pipeline::PrimitiveTopology;
+
+use crate::SkyboxMaterial;
+
+/// Depth of the center of the dome's ground below the horizon, relative to the dome's radius. The
+/// ground is a shallow cone rather than a flat disc so that every point on it is in a direction
+/// below the horizon, which the shader's ground fade relies on.
+const GROUND_DEPTH: f32 = 0.3;
+
+/// Builds a unit hemisphere above the horizon, closed off by a shallow ground cone, with its faces
+/// pointing outwards like the skybox cube's.
+pub(crate) fn dome(segments: u32, rings: u32) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    for ring in 0..=rings {
+        let elevation = ring as f32 / rings as f32 * FRAC_PI_2;
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        for segment in 0..=segments {
+            let azimuth = segment as f32 / segments as f32 * TAU;
+            let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+            let position = [
+                cos_elevation * cos_azimuth,
+                sin_elevation,
+                cos_elevation * sin_azimuth,
+            ];
+            positions.push(position);
+            normals.push(position);
+            uvs.push([
+                segment as f32 / segments as f32,
+                1.0 - ring as f32 / rings as f32,
+            ]);
+        }
+    }
+    let row = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let bottom = ring * row + segment;
+            let top = bottom + row;
+            indices.extend_from_slice(&[bottom, top, bottom + 1]);
+            // The top ring is all at the zenith, so the second triangle of its quads is empty.
+            if ring + 1 < rings {
+                indices.extend_from_slice(&[bottom + 1, top, top + 1]);
+            }
+        }
+    }
+
+    // The ground shares the positions of the hemisphere's bottom ring, which comes first.
+    let center = positions.len() as u32;
+    positions.push([0.0, -GROUND_DEPTH, 0.0]);
+    normals.push([0.0, -1.0, 0.0]);
+    uvs.push([0.5, 1.0]);
+    for segment in 0..segments {
+        indices.extend_from_slice(&[center, segment, segment + 1]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// System which warns about skybox meshes that don't surround the origin, since parts of the sky
+/// would be missing. Each mesh is checked once, after it has loaded.
+pub(crate) fn check_skybox_meshes(
+    meshes: Res<Assets<Mesh>>,
+    mut checked: Local<HashSet<HandleId>>,
+    skyboxes: Query<&Handle<Mesh>, With<Handle<SkyboxMaterial>>>,
+) {
+    for handle in skyboxes.iter() {
+        if checked.contains(&handle.id) {
+            continue;
+        }
+        let mesh = match meshes.get(handle) {
+            Some(mesh) => mesh,
+            None => continue,
+        };
+        checked.insert(handle.id);
+        let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => positions,
+            _ => {
+                warn!("Skybox mesh {:?} has no vertex positions", handle.id);
+                continue;
+            }
+        };
+        // This only checks the bounds, so it can't catch every mesh with holes, but it does catch
+        // meshes which are off to one side of the origin, like most meshes made for other uses.
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), &position| (min.min(position.into()), max.max(position.into())),
+        );
+        if !(min.cmplt(Vec3::ZERO).all() && max.cmpgt(Vec3::ZERO).all()) {
+            warn!(
+                "Skybox mesh {:?} doesn't surround the origin (bounds {:?} to {:?}), so parts of \
+                 the sky won't be drawn",
+                handle.id, min, max
+            );
+        }
+    }
+}
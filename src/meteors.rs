@@ -0,0 +1,221 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shooting stars for night skies.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::random::Rng;
+use crate::{SkyboxMaterial, TimeOfDay};
+
+/// Component which makes occasional shooting stars streak across a skybox.
+///
+/// Meteors are drawn by the skybox shader through the `meteor_*` fields of the skybox's
+/// [`SkyboxMaterial`], so they always appear behind everything else in the scene. Only one meteor
+/// is shown at a time, and all skyboxes sharing the material show the same meteors.
+///
+/// If the [`SkyboxDayNightPlugin`](crate::SkyboxDayNightPlugin) is running, meteors fade out during
+/// the day along with the night sky.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMeteors};
+/// # fn setup(mut commands: Commands) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::default())
+///     // A shooting star every 20 seconds on average.
+///     .insert(SkyboxMeteors::new(20.0));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SkyboxMeteors {
+    /// Average time between meteors, in seconds.
+    pub mean_interval: f32,
+    /// How fast meteors move across the sky, in radians per second.
+    pub speed: f32,
+    /// How long each meteor is visible, in seconds.
+    pub duration: f32,
+    /// Length of the meteor's trail, in radians.
+    pub trail_length: f32,
+    /// Angular half-width of the meteor's streak, in radians.
+    pub width: f32,
+    /// Peak brightness of the meteors.
+    pub brightness: f32,
+    /// Color of the meteors.
+    pub color: Color,
+    /// Range of elevations, in radians above the horizon, where meteors appear.
+    pub elevation: (f32, f32),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rng: Rng,
+    /// Time until the next meteor.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    countdown: Option<f32>,
+    /// The meteor currently being shown.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    active: Option<Meteor>,
+}
+
+impl SkyboxMeteors {
+    /// Creates a `SkyboxMeteors` with a meteor on average every `mean_interval` seconds.
+    pub fn new(mean_interval: f32) -> Self {
+        Self {
+            mean_interval,
+            ..Default::default()
+        }
+    }
+
+    /// Reseeds the random number generator used to place the meteors.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Starts a meteor at `start`, moving towards `heading`. `heading` doesn't need to be exactly
+    /// perpendicular to `start`, but must not be parallel to it. Replaces any meteor currently
+    /// being shown.
+    pub fn launch(&mut self, start: Vec3, heading: Vec3) {
+        let start = start.normalize();
+        let heading = (heading - start * heading.dot(start)).normalize();
+        self.active = Some(Meteor {
+            start,
+            heading,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Whether a meteor is currently being shown.
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Picks a random starting point and heading and launches a meteor.
+    fn launch_random(&mut self) {
+        let start = self.rng.direction(self.elevation);
+        // Most meteors fall towards the horizon, so pick a heading in the lower half of the
+        // directions tangent to the sky at the start. Straight overhead, every direction is
+        // towards the horizon, so any will do.
+        let across = Vec3::Y.cross(start);
+        let across = if across.length_squared() > 1e-6 {
+            across.normalize()
+        } else {
+            start.any_orthonormal_vector()
+        };
+        let down = across.cross(start);
+        let angle = self.rng.range(0.2, PI - 0.2);
+        self.launch(start, across * angle.cos() + down * angle.sin());
+    }
+
+    /// Picks the time until the next meteor.
+    fn random_countdown(&mut self) -> f32 {
+        self.rng.exponential(self.mean_interval)
+    }
+}
+
+impl Default for SkyboxMeteors {
+    fn default() -> Self {
+        Self {
+            mean_interval: 30.0,
+            speed: 0.6,
+            duration: 0.7,
+            trail_length: 0.15,
+            width: 0.002,
+            brightness: 1.5,
+            color: Color::rgb(1.0, 0.95, 0.85),
+            elevation: (0.3, 1.2),
+            rng: Rng::new(0x3e7e0),
+            countdown: None,
+            active: None,
+        }
+    }
+}
+
+/// Meteor currently being shown.
+#[derive(Debug, Clone)]
+struct Meteor {
+    /// Where the meteor started.
+    start: Vec3,
+    /// Direction the meteor is moving in, perpendicular to `start`.
+    heading: Vec3,
+    elapsed: f32,
+}
+
+impl Meteor {
+    /// Position on the meteor's great circle `angle` radians after its start.
+    fn at(&self, angle: f32) -> Vec3 {
+        self.start * angle.cos() + self.heading * angle.sin()
+    }
+}
+
+/// System which runs [`SkyboxMeteors`].
+pub(crate) fn run_meteors(
+    time: Res<Time>,
+    time_of_day: Option<Res<TimeOfDay>>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut skyboxes: Query<(&mut SkyboxMeteors, &Handle<SkyboxMaterial>)>,
+) {
+    let delta = time.delta_seconds();
+    let night = time_of_day.map_or(1.0, |time_of_day| 1.0 - time_of_day.daylight());
+    for (mut meteors, handle) in skyboxes.iter_mut() {
+        let meteors = &mut *meteors;
+        if let Some(meteor) = &mut meteors.active {
+            meteor.elapsed += delta;
+            if meteor.elapsed >= meteors.duration {
+                meteors.active = None;
+            }
+        }
+
+        let countdown = match meteors.countdown {
+            Some(countdown) => countdown - delta,
+            None => meteors.random_countdown(),
+        };
+        meteors.countdown = Some(countdown);
+        if countdown <= 0.0 {
+            meteors.countdown = Some(meteors.random_countdown());
+            if meteors.active.is_none() {
+                meteors.launch_random();
+            }
+        }
+
+        let (head, tail, brightness) = match &meteors.active {
+            Some(meteor) => {
+                let travelled = meteor.elapsed * meteors.speed;
+                let head = meteor.at(travelled);
+                let tail = meteor.at((travelled - meteors.trail_length).max(0.0));
+                // Flare up and then burn out over the meteor's lifetime.
+                let fade = (meteor.elapsed / meteors.duration * PI).sin();
+                (head, tail, meteors.brightness * fade * night)
+            }
+            None => (Vec3::Y, Vec3::Y, 0.0),
+        };
+        // Avoid re-uploading the material every frame while there's no meteor.
+        match materials.get(handle) {
+            Some(material) if brightness != 0.0 || material.meteor_brightness != 0.0 => {}
+            _ => continue,
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.meteor_head = head;
+            material.meteor_tail = tail;
+            material.meteor_width = meteors.width;
+            material.meteor_brightness = brightness;
+            material.meteor_color = meteors.color;
+        }
+    }
+}
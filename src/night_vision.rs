@@ -0,0 +1,90 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Night-vision rendering of the sky.
+
+use bevy::asset::HandleId;
+use bevy::prelude::*;
+
+use crate::SkyboxMaterial;
+
+/// Settings for drawing a sky as seen through night-vision goggles, set on
+/// [`SkyboxMaterial::night_vision`]. The sky is turned monochrome, brightened, tinted, and covered
+/// in flickering noise, so it matches a night-vision overlay without a separate post-processing
+/// pass. Everything drawn on the sky, such as the sun and lightning, goes through it too.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{NightVision, SkyboxMaterial};
+/// # fn toggle_goggles(
+/// #     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// #     skybox: Handle<SkyboxMaterial>,
+/// # ) {
+/// let material = skyboxes.get_mut(skybox).unwrap();
+/// material.night_vision = match material.night_vision {
+///     Some(_) => None,
+///     None => Some(NightVision::default()),
+/// };
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NightVision {
+    /// Color of the monochrome image at full brightness.
+    pub color: Color,
+    /// How much to brighten the sky by. Night skies are very dark, so this is usually well above
+    /// `1.0`.
+    pub gain: f32,
+    /// Strength of the noise, as a fraction of full brightness.
+    pub noise: f32,
+    /// Seed for the noise pattern. The [`SkyboxPlugin`](crate::SkyboxPlugin) changes this every
+    /// frame so the noise flickers, so it doesn't need to be set.
+    pub noise_seed: f32,
+}
+
+impl Default for NightVision {
+    fn default() -> Self {
+        Self {
+            color: Color::rgb(0.3, 1.0, 0.3),
+            gain: 8.0,
+            noise: 0.15,
+            noise_seed: 0.0,
+        }
+    }
+}
+
+/// System which changes the noise seed of materials using night vision every frame.
+pub(crate) fn animate_night_vision(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut ids: Local<Vec<HandleId>>,
+) {
+    ids.clear();
+    ids.extend(
+        materials
+            .iter()
+            .filter(|(_, material)| material.night_vision.is_some())
+            .map(|(id, _)| id),
+    );
+    // Wrapping keeps the seed small enough for the shader's hash to stay precise.
+    let seed = (time.seconds_since_startup() % 1000.0) as f32;
+    for id in ids.iter() {
+        if let Some(night_vision) = materials
+            .get_mut(*id)
+            .and_then(|material| material.night_vision.as_mut())
+        {
+            night_vision.noise_seed = seed;
+        }
+    }
+}
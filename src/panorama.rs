@@ -0,0 +1,380 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Converting between skybox textures and panoramas.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::cube::{direction_to_face_uv, face_uv_to_direction, FACES};
+
+/// Converts a skybox texture into an equirectangular panorama `width` pixels wide and half as
+/// high, the format used by 360° image viewers and photo sites. The middle of the panorama faces
+/// the front (-Z) of the skybox, with the right (+X) face to its right.
+///
+/// The skybox texture can be either a 6-layer array texture or a stacked `N` by `6*N` image which
+/// hasn't been converted yet, and must be in `Rgba8UnormSrgb` or `Rgba8Unorm` format. The panorama
+/// has the same format, and only the first sky of a texture holding several is used. Returns
+/// `None` if the texture isn't in a supported format.
+///
+/// The panorama can be saved with the `image` crate, for example with
+/// `image::save_buffer("sky.png", &panorama.data, width, width / 2, image::ColorType::Rgba8)`.
+pub fn skybox_to_equirectangular(skybox: &Texture, width: u32) -> Option<Texture> {
+    if !is_rgba8(skybox.format) {
+        return None;
+    }
+    let size = skybox.size.width;
+    let face_len = (size * size) as usize * 4;
+    if size == 0 || skybox.data.len() < face_len * FACES {
+        return None;
+    }
+
+    let height = width / 2;
+    let mut data = Vec::with_capacity((width * height) as usize * 4);
+    for y in 0..height {
+        let latitude = FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * PI;
+        let (sin_latitude, cos_latitude) = latitude.sin_cos();
+        for x in 0..width {
+            let longitude = (x as f32 + 0.5) / width as f32 * 2.0 * PI - PI;
+            let (sin_longitude, cos_longitude) = longitude.sin_cos();
+            let direction = Vec3::new(
+                sin_longitude * cos_latitude,
+                sin_latitude,
+                -cos_longitude * cos_latitude,
+            );
+            let (face, uv) = direction_to_face_uv(direction);
+            let face = &skybox.data[face * face_len..(face + 1) * face_len];
+            let color = sample_bilinear(face, size, size, uv * size as f32, false);
+            data.extend_from_slice(&to_rgba8(color));
+        }
+    }
+    Some(Texture::new(
+        Extent3d::new(width, height, 1),
+        TextureDimension::D2,
+        data,
+        skybox.format,
+    ))
+}
+
+/// Converts an equirectangular panorama, twice as wide as it is high, into a skybox texture with
+/// faces `size` pixels wide, laid out like the panoramas from [`skybox_to_equirectangular`]. The
+/// panorama must be in `Rgba8UnormSrgb` or `Rgba8Unorm` format, and the skybox texture has the same
+/// format, as a 6-layer array texture. Returns `None` if the panorama isn't in a supported format.
+///
+/// Each pixel of the skybox averages `supersampling` by `supersampling` samples of the panorama, so
+/// detailed panoramas which are much bigger than the skybox don't alias or show moiré patterns.
+/// `1` takes a single sample at the middle of each pixel, which is enough when the panorama isn't
+/// much bigger than the skybox; `2` or `3` is plenty otherwise.
+pub fn equirectangular_to_skybox(
+    panorama: &Texture,
+    size: u32,
+    supersampling: u32,
+) -> Option<Texture> {
+    let (width, height) = (panorama.size.width, panorama.size.height);
+    if !is_rgba8(panorama.format)
+        || width == 0
+        || height == 0
+        || panorama.data.len() < (width * height) as usize * 4
+    {
+        return None;
+    }
+    let mut data = Vec::with_capacity((size * size) as usize * FACES * 4);
+    panorama_to_faces(
+        &panorama.data,
+        width,
+        height,
+        size,
+        supersampling,
+        &mut data,
+    );
+    Some(Texture::new(
+        Extent3d::new(size, size, FACES as u32),
+        TextureDimension::D2,
+        data,
+        panorama.format,
+    ))
+}
+
+/// Converts an over-under stereo panorama, as wide as it is high, into a skybox texture holding a
+/// sky for each eye, for 360° stereo photos in VR. The top half of the panorama is the left eye's
+/// view and the bottom half the right eye's, each an equirectangular panorama laid out as for
+/// [`equirectangular_to_skybox`], which explains the other parameters.
+///
+/// The left eye's sky is sky `0` of the texture and the right eye's is sky `1`, like the textures
+/// from [`make_multi_sky_array`](crate::SkyboxTextureConversion::make_multi_sky_array). Give
+/// each eye's camera a [`SkyboxCameraTint`](crate::SkyboxCameraTint) with its sky as the `layer` so
+/// each eye sees its own half. Returns `None` if the panorama isn't in a supported format.
+pub fn stereo_equirectangular_to_skybox(
+    panorama: &Texture,
+    size: u32,
+    supersampling: u32,
+) -> Option<Texture> {
+    let (width, height) = (panorama.size.width, panorama.size.height / 2);
+    let eye_len = (width * height) as usize * 4;
+    if !is_rgba8(panorama.format) || width == 0 || height == 0 || panorama.data.len() < eye_len * 2
+    {
+        return None;
+    }
+    let mut data = Vec::with_capacity((size * size) as usize * FACES * 2 * 4);
+    for eye in panorama.data[..eye_len * 2].chunks_exact(eye_len) {
+        panorama_to_faces(eye, width, height, size, supersampling, &mut data);
+    }
+    Some(Texture::new(
+        Extent3d::new(size, size, FACES as u32 * 2),
+        TextureDimension::D2,
+        data,
+        panorama.format,
+    ))
+}
+
+/// Samples an equirectangular panorama onto the six faces of a skybox `size` pixels wide, appending
+/// them to `data`.
+fn panorama_to_faces(
+    panorama: &[u8],
+    width: u32,
+    height: u32,
+    size: u32,
+    supersampling: u32,
+    data: &mut Vec<u8>,
+) {
+    let samples = supersampling.max(1);
+    for face in 0..FACES {
+        for y in 0..size {
+            for x in 0..size {
+                let mut total = Vec4::ZERO;
+                for sy in 0..samples {
+                    for sx in 0..samples {
+                        let offset =
+                            (Vec2::new(sx as f32, sy as f32) + Vec2::splat(0.5)) / samples as f32;
+                        let uv = (Vec2::new(x as f32, y as f32) + offset) / size as f32;
+                        let direction = face_uv_to_direction(face, uv).normalize();
+                        let longitude = direction.x.atan2(-direction.z);
+                        let latitude = direction.y.clamp(-1.0, 1.0).asin();
+                        let position = Vec2::new(
+                            (longitude + PI) / (2.0 * PI) * width as f32,
+                            (FRAC_PI_2 - latitude) / PI * height as f32,
+                        );
+                        total += sample_bilinear(panorama, width, height, position, true);
+                    }
+                }
+                data.extend_from_slice(&to_rgba8(total / (samples * samples) as f32));
+            }
+        }
+    }
+}
+
+/// Converts a skybox texture into a square fisheye image `size` pixels across, in the domemaster
+/// format used by planetariums and other dome projection systems. The center of the image looks
+/// straight up (+Y), and the edge of the circle is `field_of_view / 2.0` radians from it, so a
+/// `field_of_view` of `PI` shows the whole upper half of the sky down to the horizon. The front
+/// (-Z) of the skybox is at the bottom of the image, with the right (+X) face to the right, as the
+/// dome is seen by an audience facing the front. Angles from the center are spread evenly across
+/// the circle, and the corners outside it are transparent black.
+///
+/// The skybox texture has the same requirements as for [`skybox_to_equirectangular`], and the
+/// fisheye image has the same format. Returns `None` if the texture isn't in a supported format.
+pub fn skybox_to_fisheye(skybox: &Texture, size: u32, field_of_view: f32) -> Option<Texture> {
+    if !is_rgba8(skybox.format) {
+        return None;
+    }
+    let face_size = skybox.size.width;
+    let face_len = (face_size * face_size) as usize * 4;
+    if face_size == 0 || skybox.data.len() < face_len * FACES {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity((size * size) as usize * 4);
+    for y in 0..size {
+        for x in 0..size {
+            let offset =
+                (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) / size as f32 * 2.0 - Vec2::ONE;
+            let radius = offset.length();
+            if radius > 1.0 {
+                data.extend_from_slice(&[0; 4]);
+                continue;
+            }
+            let (sin_angle, cos_angle) = (radius * field_of_view / 2.0).sin_cos();
+            // Down the image is towards the front of the skybox.
+            let across = Vec3::new(offset.x, 0.0, -offset.y).normalize_or_zero();
+            let direction = across * sin_angle + Vec3::Y * cos_angle;
+            let (face, uv) = direction_to_face_uv(direction);
+            let face = &skybox.data[face * face_len..(face + 1) * face_len];
+            let color = sample_bilinear(face, face_size, face_size, uv * face_size as f32, false);
+            data.extend_from_slice(&to_rgba8(color));
+        }
+    }
+    Some(Texture::new(
+        Extent3d::new(size, size, 1),
+        TextureDimension::D2,
+        data,
+        skybox.format,
+    ))
+}
+
+/// Whether textures in `format` can be converted.
+fn is_rgba8(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    )
+}
+
+/// Samples an RGBA8 image at `position` in pixels, blending the four nearest pixels. If `wrap` is
+/// set, the image wraps around horizontally, otherwise it is clamped at the edges. Pixels are
+/// blended in their stored encoding, like most image resizing does.
+fn sample_bilinear(image: &[u8], width: u32, height: u32, position: Vec2, wrap: bool) -> Vec4 {
+    let x = position.x - 0.5;
+    let y = (position.y - 0.5).clamp(0.0, height as f32 - 1.0);
+    let x = if wrap {
+        x.rem_euclid(width as f32)
+    } else {
+        x.clamp(0.0, width as f32 - 1.0)
+    };
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = ((x0 as u32).min(width - 1), y0 as u32);
+    let x1 = if wrap {
+        (x0 + 1) % width
+    } else {
+        (x0 + 1).min(width - 1)
+    };
+    let y1 = (y0 + 1).min(height - 1);
+    let pixel = |x: u32, y: u32| {
+        let i = ((y * width + x) * 4) as usize;
+        Vec4::new(
+            image[i] as f32,
+            image[i + 1] as f32,
+            image[i + 2] as f32,
+            image[i + 3] as f32,
+        )
+    };
+    let top = pixel(x0, y0).lerp(pixel(x1, y0), fx);
+    let bottom = pixel(x0, y1).lerp(pixel(x1, y1), fx);
+    top.lerp(bottom, fy)
+}
+
+/// Rounds a color with channels from `0.0` to `255.0` to RGBA8.
+fn to_rgba8(color: Vec4) -> [u8; 4] {
+    [
+        color.x.round() as u8,
+        color.y.round() as u8,
+        color.z.round() as u8,
+        color.w.round() as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Colors of each face of the test skybox.
+    const FACE_COLORS: [[u8; 4]; FACES] = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 0, 255],
+        [0, 255, 255, 255],
+        [255, 0, 255, 255],
+    ];
+
+    /// Creates a skybox texture with each face a solid color.
+    fn solid_faces(size: u32) -> Texture {
+        let mut data = Vec::new();
+        for color in &FACE_COLORS {
+            for _ in 0..size * size {
+                data.extend_from_slice(color);
+            }
+        }
+        Texture::new(
+            Extent3d::new(size, size, FACES as u32),
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    /// Gets the color of the pixel of `texture` at `x`, `y` on `layer`.
+    fn pixel(texture: &Texture, layer: u32, x: u32, y: u32) -> [u8; 4] {
+        let size = texture.size;
+        let i = (((layer * size.height + y) * size.width + x) * 4) as usize;
+        [
+            texture.data[i],
+            texture.data[i + 1],
+            texture.data[i + 2],
+            texture.data[i + 3],
+        ]
+    }
+
+    /// Checks that the pixels of a `width` pixel wide panorama looking along each axis show the
+    /// color of that axis's face.
+    fn assert_axes_show_face_colors(panorama: &Texture, width: u32) {
+        let height = width / 2;
+        assert_eq!(panorama.size, Extent3d::new(width, height, 1));
+        // In the same order as the faces.
+        let axes = [
+            (width * 3 / 4, height / 2),
+            (width / 4, height / 2),
+            (width / 2, 0),
+            (width / 2, height - 1),
+            (0, height / 2),
+            (width / 2, height / 2),
+        ];
+        for (face, &(x, y)) in axes.iter().enumerate() {
+            assert_eq!(pixel(panorama, 0, x, y), FACE_COLORS[face], "face {}", face);
+        }
+    }
+
+    #[test]
+    fn equirectangular_shows_face_colors() {
+        let panorama = skybox_to_equirectangular(&solid_faces(8), 64).unwrap();
+        assert_axes_show_face_colors(&panorama, 64);
+    }
+
+    #[test]
+    fn equirectangular_round_trip_keeps_face_colors() {
+        let size = 16;
+        let panorama = skybox_to_equirectangular(&solid_faces(size), 256).unwrap();
+        let skybox = equirectangular_to_skybox(&panorama, size, 2).unwrap();
+        assert_eq!(skybox.size, Extent3d::new(size, size, FACES as u32));
+        // Pixels near the edges of faces blend with the neighboring faces, so only check the middle
+        // of each face.
+        for face in 0..FACES as u32 {
+            for y in size / 4..size * 3 / 4 {
+                for x in size / 4..size * 3 / 4 {
+                    assert_eq!(
+                        pixel(&skybox, face, x, y),
+                        FACE_COLORS[face as usize],
+                        "pixel {}, {} of face {}",
+                        x,
+                        y,
+                        face,
+                    );
+                }
+            }
+        }
+
+        let round_trip = skybox_to_equirectangular(&skybox, 256).unwrap();
+        assert_axes_show_face_colors(&round_trip, 256);
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        let mut skybox = solid_faces(4);
+        skybox.format = TextureFormat::Rgba32Float;
+        assert!(skybox_to_equirectangular(&skybox, 16).is_none());
+        assert!(equirectangular_to_skybox(&skybox, 4, 1).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rain and snow streaking across the sky.
+
+use bevy::asset::HandleId;
+use bevy::prelude::*;
+
+use crate::{SkyWind, SkyboxMaterial};
+
+/// How many repeats of the streak pattern scroll past each second for every meter per second the
+/// precipitation moves at. The pattern repeats four times per radian in `skybox.frag`.
+const STREAK_SCROLL_RATE: f32 = 0.05;
+
+/// Settings for distant rain or snow drawn over the sky, set on
+/// [`SkyboxMaterial::precipitation`]. Streaks fall across the whole sky, so precipitation is
+/// visible in the distance before any particles near the camera start.
+///
+/// The streaks fall in the direction the precipitation moves, straight down pushed sideways by the
+/// `wind`, so they lean with it and converge towards a point below the horizon downwind. The
+/// [`SkyboxPlugin`](crate::SkyboxPlugin) scrolls them every frame at a speed set by how fast the
+/// precipitation moves.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{Precipitation, SkyboxMaterial};
+/// # fn setup(mut skyboxes: ResMut<Assets<SkyboxMaterial>>, skybox: Handle<SkyboxMaterial>) {
+/// // Snow blowing in from the east.
+/// skyboxes.get_mut(skybox).unwrap().precipitation = Some(Precipitation {
+///     wind: Vec3::new(-2.0, 0.0, 0.0),
+///     ..Precipitation::snow()
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Precipitation {
+    /// Color of the streaks. The alpha sets how strongly they cover the sky behind them.
+    pub color: Color,
+    /// Fraction of the sky the streaks fall over, from `0.0` to `1.0`.
+    pub density: f32,
+    /// World-space wind velocity in meters per second, which pushes the precipitation sideways.
+    /// Only its horizontal part is used. This is kept in step with the [`SkyWind`] resource
+    /// while there is one.
+    pub wind: Vec3,
+    /// Speed in meters per second the precipitation falls at in still air. Rain falls at about
+    /// `9.0`, and snow at about `1.0`.
+    pub fall_speed: f32,
+    /// Fraction of each streak's path which it covers, from `0.0` to `1.0`. Rain makes long
+    /// streaks, while snowflakes are closer to dots.
+    pub streak_length: f32,
+    /// How far the streaks have scrolled, from `0.0` to `1.0`. The
+    /// [`SkyboxPlugin`](crate::SkyboxPlugin) advances this every frame, so it doesn't need to be
+    /// set.
+    pub scroll: f32,
+}
+
+impl Precipitation {
+    /// Settings for rain.
+    pub fn rain() -> Self {
+        Self {
+            color: Color::rgba(0.7, 0.75, 0.8, 0.35),
+            density: 0.5,
+            wind: Vec3::ZERO,
+            fall_speed: 9.0,
+            streak_length: 0.15,
+            scroll: 0.0,
+        }
+    }
+
+    /// Settings for snow.
+    pub fn snow() -> Self {
+        Self {
+            color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+            density: 0.4,
+            wind: Vec3::ZERO,
+            fall_speed: 1.0,
+            streak_length: 0.03,
+            scroll: 0.0,
+        }
+    }
+
+    /// World-space velocity of the precipitation.
+    fn velocity(&self) -> Vec3 {
+        Vec3::new(self.wind.x, -self.fall_speed, self.wind.z)
+    }
+
+    /// Packs the settings for the `SkyboxMaterial_params` uniform block. The order must match the
+    /// block in `skybox.frag`.
+    pub(crate) fn params(&self) -> [Vec4; 3] {
+        let direction = self.velocity().normalize_or_zero();
+        [
+            self.color.as_linear_rgba_f32().into(),
+            direction.extend(self.density.clamp(0.0, 1.0)),
+            Vec4::new(self.scroll, self.streak_length.clamp(0.0, 1.0), 0.0, 0.0),
+        ]
+    }
+}
+
+impl Default for Precipitation {
+    /// Rain.
+    fn default() -> Self {
+        Self::rain()
+    }
+}
+
+/// System which scrolls the streaks of materials with precipitation, and blows them with the
+/// [`SkyWind`].
+pub(crate) fn animate_precipitation(
+    time: Res<Time>,
+    wind: Option<Res<SkyWind>>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut ids: Local<Vec<HandleId>>,
+) {
+    ids.clear();
+    ids.extend(
+        materials
+            .iter()
+            .filter(|(_, material)| material.precipitation.is_some())
+            .map(|(id, _)| id),
+    );
+    for id in ids.iter() {
+        if let Some(precipitation) = materials
+            .get_mut(*id)
+            .and_then(|material| material.precipitation.as_mut())
+        {
+            if let Some(wind) = &wind {
+                precipitation.wind = wind.velocity();
+            }
+            let speed = precipitation.velocity().length();
+            // Wrapping keeps the scroll small enough for the shader to stay precise.
+            precipitation.scroll = (precipitation.scroll
+                + time.delta_seconds() * speed * STREAK_SCROLL_RATE)
+                .rem_euclid(1.0);
+        }
+    }
+}
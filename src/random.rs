@@ -0,0 +1,83 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Small seedable random number generator for sky effects.
+
+use std::f32::consts::TAU;
+
+use bevy::math::Vec3;
+
+/// Seedable pseudo-random number generator (PCG32). This is only used for visual effects, so it
+/// doesn't need to be cryptographically secure, but it does need to be deterministic for a given
+/// seed.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+    const INCREMENT: u64 = 1442695040888963407;
+
+    /// Creates a generator from a seed.
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Returns the next random `u32`.
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// Returns a random `f32` in `[0, 1)`.
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        // Use the top 24 bits, which is as many as an f32 can represent exactly.
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a random `f32` in `[min, max)`.
+    pub(crate) fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Returns a random direction in the sky, at any azimuth and an elevation in radians within
+    /// `elevation`.
+    pub(crate) fn direction(&mut self, elevation: (f32, f32)) -> Vec3 {
+        let azimuth = self.range(0.0, TAU);
+        let elevation = self.range(elevation.0, elevation.1);
+        let (sin_azimuth, cos_azimuth) = azimuth.sin_cos();
+        let (sin_elevation, cos_elevation) = elevation.sin_cos();
+        Vec3::new(
+            cos_elevation * cos_azimuth,
+            sin_elevation,
+            cos_elevation * sin_azimuth,
+        )
+    }
+
+    /// Returns a random time until the next of a series of events which happen `mean` apart on
+    /// average. The times are exponentially distributed, so events are evenly likely at any moment.
+    pub(crate) fn exponential(&mut self, mean: f32) -> f32 {
+        -(1.0 - self.next_f32()).ln() * mean
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Components for animating the rotation of skyboxes.
+
+use bevy::prelude::*;
+
+/// Component which continuously spins a skybox, e.g. for slowly drifting clouds or stars. The
+/// [`SkyboxPlugin`](crate::SkyboxPlugin) rotates the `Transform` of any entity with this component
+/// by `speed` radians per second around `axis`.
+///
+/// A skybox should usually only have one component setting its rotation. If it has several, the
+/// one used is, from highest precedence to lowest: [`SkyboxReorientation`] while it's turning,
+/// [`SkyboxFollowRotation`], [`SkyboxOrbitRotation`], then `SkyboxRotation`.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, SkyboxRotation};
+/// # fn setup(mut commands: Commands, mut skyboxes: ResMut<Assets<SkyboxMaterial>>) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())))
+///     // Do a full turn around the vertical axis every 10 minutes.
+///     .insert(SkyboxRotation::new(Vec3::Y, std::f32::consts::TAU / 600.0));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxRotation {
+    /// Axis to rotate around. Doesn't need to be normalized, but must not be zero.
+    pub axis: Vec3,
+    /// Rotation speed in radians per second. Negative speeds rotate the other way.
+    pub speed: f32,
+}
+
+impl SkyboxRotation {
+    /// Creates a `SkyboxRotation` spinning around `axis` at `speed` radians per second.
+    pub fn new(axis: Vec3, speed: f32) -> Self {
+        Self { axis, speed }
+    }
+}
+
+impl Default for SkyboxRotation {
+    /// Doesn't rotate at all until a `speed` is set.
+    fn default() -> Self {
+        Self {
+            axis: Vec3::Y,
+            speed: 0.0,
+        }
+    }
+}
+
+/// System which applies [`SkyboxRotation`]s.
+#[allow(clippy::type_complexity)]
+pub(crate) fn rotate_skyboxes(
+    time: Res<Time>,
+    mut skyboxes: Query<
+        (&SkyboxRotation, &mut Transform),
+        (
+            Without<SkyboxReorientation>,
+            Without<SkyboxFollowRotation>,
+            Without<SkyboxOrbitRotation>,
+        ),
+    >,
+) {
+    for (rotation, mut transform) in skyboxes.iter_mut() {
+        if rotation.speed == 0.0 {
+            continue;
+        }
+        transform.rotate(Quat::from_axis_angle(
+            rotation.axis.normalize(),
+            rotation.speed * time.delta_seconds(),
+        ));
+    }
+}
+
+/// Component which smoothly turns a skybox to a new orientation, for example to re-orient the sky
+/// for a cutscene or to wheel the stars overhead.
+///
+/// When this is added to a skybox entity, the [`SkyboxPlugin`](crate::SkyboxPlugin) turns the
+/// skybox's `Transform` from its current rotation to `target` over `duration` seconds, easing in
+/// and out so there's no visible jolt at either end. Once it arrives, this component is removed.
+/// Inserting a new one while a turn is in progress starts the new turn from wherever the skybox
+/// is. While turning, this overrides any other component rotating the same skybox, such as a
+/// [`SkyboxRotation`] or [`SkyboxFollowRotation`], which take over again once it arrives.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyboxReorientation;
+/// # fn tilt_sky(mut commands: Commands, skybox: Entity) {
+/// commands.entity(skybox).insert(SkyboxReorientation::new(
+///     Quat::from_rotation_x(std::f32::consts::FRAC_PI_4),
+///     5.0,
+/// ));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkyboxReorientation {
+    /// Rotation to turn the skybox to.
+    pub target: Quat,
+    /// How long the turn takes, in seconds.
+    pub duration: f32,
+    /// How long the turn has been running, in seconds.
+    elapsed: f32,
+    /// Rotation of the skybox when the turn started.
+    from: Option<Quat>,
+}
+
+impl SkyboxReorientation {
+    /// Creates a `SkyboxReorientation` turning to `target` over `duration` seconds.
+    pub fn new(target: Quat, duration: f32) -> Self {
+        Self {
+            target,
+            duration,
+            elapsed: 0.0,
+            from: None,
+        }
+    }
+
+    /// How far along the turn is, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// System which runs [`SkyboxReorientation`]s.
+pub(crate) fn reorient_skyboxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut skyboxes: Query<(Entity, &mut SkyboxReorientation, &mut Transform)>,
+) {
+    for (entity, mut reorientation, mut transform) in skyboxes.iter_mut() {
+        let from = match reorientation.from {
+            Some(from) => {
+                reorientation.elapsed += time.delta_seconds();
+                from
+            }
+            None => {
+                reorientation.from = Some(transform.rotation);
+                transform.rotation
+            }
+        };
+        let t = reorientation.progress();
+        transform.rotation = from.slerp(reorientation.target, t * t * (3.0 - 2.0 * t));
+        if t >= 1.0 {
+            transform.rotation = reorientation.target;
+            commands.entity(entity).remove::<SkyboxReorientation>();
+        }
+    }
+}
+
+/// Component which keeps a skybox's rotation matched to another entity's, such as a player's ship
+/// in a space game, so the sky turns along with it. The rotation can be scaled, for example by
+/// `-1.0` to counter-rotate with the interior of a spinning space station so the stars outside
+/// appear to turn.
+///
+/// The [`SkyboxPlugin`](crate::SkyboxPlugin) sets the skybox's rotation from the world-space
+/// rotation of `target`, including its parents, every frame. To avoid lagging a frame behind,
+/// systems which turn the target should run before
+/// [`SkyboxSystem::Animate`](crate::SkyboxSystem::Animate). This overrides any [`SkyboxRotation`]
+/// or [`SkyboxOrbitRotation`] on the same skybox, but is itself paused while a
+/// [`SkyboxReorientation`] is turning it. If the target is despawned, the skybox keeps its last
+/// rotation.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxFollowRotation, SkyboxMaterial};
+/// # fn setup(
+/// #     mut commands: Commands,
+/// #     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// #     station: Entity,
+/// # ) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())))
+///     .insert(SkyboxFollowRotation::new(station).with_scale(-1.0));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkyboxFollowRotation {
+    /// Entity whose rotation to follow.
+    pub target: Entity,
+    /// How much of the target's rotation to apply: `1.0` matches it, `0.5` turns half as far, and
+    /// negative values turn the other way.
+    pub scale: f32,
+}
+
+impl SkyboxFollowRotation {
+    /// Creates a `SkyboxFollowRotation` matching `target`'s rotation.
+    pub fn new(target: Entity) -> Self {
+        Self { target, scale: 1.0 }
+    }
+
+    /// Scales the followed rotation by `scale`.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+/// System which applies [`SkyboxFollowRotation`]s.
+pub(crate) fn follow_rotations(
+    mut skyboxes: Query<(&SkyboxFollowRotation, &mut Transform), Without<SkyboxReorientation>>,
+    targets: Query<(&Transform, Option<&Parent>), Without<SkyboxFollowRotation>>,
+) {
+    for (follow, mut transform) in skyboxes.iter_mut() {
+        // GlobalTransforms aren't updated until after this runs, so build the world-space rotation
+        // from the local ones.
+        let mut rotation = Quat::IDENTITY;
+        let mut entity = Some(follow.target);
+        let mut found = false;
+        while let Some((local, parent)) = entity.and_then(|entity| targets.get(entity).ok()) {
+            rotation = local.rotation * rotation;
+            entity = parent.map(|parent| parent.0);
+            found = true;
+        }
+        if !found {
+            continue;
+        }
+        let rotation = if follow.scale == 1.0 {
+            rotation
+        } else {
+            let (axis, angle) = rotation.to_axis_angle();
+            Quat::from_axis_angle(axis, angle * follow.scale)
+        };
+        transform.rotation = rotation.normalize();
+    }
+}
+
+/// Component which turns a skybox of the stars as seen from the surface of a planet, such as in a
+/// space game, so the star field rises and sets through the planet's day and shifts with its
+/// seasons. The sky texture should be laid out in the star system's coordinates: +X towards the
+/// planet's vernal equinox and +Z towards the north pole of its orbit.
+///
+/// `time` is advanced by `speed` times the frame's delta every frame, and the skybox's rotation is
+/// set from it with [`planet_sky_rotation`](crate::astronomy::planet_sky_rotation), which explains
+/// the parameters. `time` may be in any units as long as `day_length` and `year_length` use the
+/// same ones, and can be set directly to jump to a time of day or year. This overrides any
+/// [`SkyboxRotation`] on the same skybox. A [`SkyboxFollowRotation`], or a [`SkyboxReorientation`]
+/// while it's turning, overrides this in turn, though `time` keeps advancing underneath.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, SkyboxOrbitRotation};
+/// # fn setup(mut commands: Commands, mut skyboxes: ResMut<Assets<SkyboxMaterial>>) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::new(skyboxes.add(SkyboxMaterial::default())))
+///     // A 20 minute day and a 10 day year, with time in seconds, at 40 degrees north.
+///     .insert(SkyboxOrbitRotation::new(1200.0, 12000.0, 30.0, 40.0));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxOrbitRotation {
+    /// Length of the planet's day, from noon to noon.
+    pub day_length: f64,
+    /// How long the planet takes to orbit its star.
+    pub year_length: f64,
+    /// Angle between the planet's axis and the pole of its orbit, in degrees.
+    pub axial_tilt: f64,
+    /// Latitude of the observer on the planet, in degrees north.
+    pub latitude: f64,
+    /// Time since midnight on the vernal equinox.
+    pub time: f64,
+    /// How much `time` advances per second. Defaults to `1.0`.
+    pub speed: f64,
+}
+
+impl SkyboxOrbitRotation {
+    /// Creates a `SkyboxOrbitRotation` starting at midnight on the vernal equinox, with time in
+    /// seconds.
+    pub fn new(day_length: f64, year_length: f64, axial_tilt: f64, latitude: f64) -> Self {
+        Self {
+            day_length,
+            year_length,
+            axial_tilt,
+            latitude,
+            time: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    /// Sets the starting `time`.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Sets how much `time` advances per second.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Rotation of the skybox at the current `time`.
+    pub fn rotation(&self) -> Quat {
+        crate::astronomy::planet_sky_rotation(
+            self.time,
+            self.day_length,
+            self.year_length,
+            self.axial_tilt,
+            self.latitude,
+        )
+    }
+}
+
+/// System which applies [`SkyboxOrbitRotation`]s.
+pub(crate) fn orbit_rotations(
+    time: Res<Time>,
+    mut skyboxes: Query<(
+        &mut SkyboxOrbitRotation,
+        &mut Transform,
+        Option<&SkyboxReorientation>,
+        Option<&SkyboxFollowRotation>,
+    )>,
+) {
+    for (mut orbit, mut transform, reorientation, follow) in skyboxes.iter_mut() {
+        orbit.time += orbit.speed * time.delta_seconds_f64();
+        // Leave the rotation to the components which take precedence.
+        if reorientation.is_none() && follow.is_none() {
+            transform.rotation = orbit.rotation();
+        }
+    }
+}
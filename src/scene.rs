@@ -0,0 +1,129 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Skyboxes spawned from scenes.
+
+use bevy::prelude::*;
+use bevy::reflect::Reflect;
+
+use crate::{SkyboxBundle, SkyboxMaterial, SkyboxTextureConversion};
+
+/// Component which turns an entity spawned from a scene into a skybox.
+///
+/// Skybox materials are created at runtime, so a scene can't refer to one directly. Instead, a
+/// scene can give an entity this component with the asset path of a skybox texture, and the
+/// [`SkyboxPlugin`](crate::SkyboxPlugin) loads the texture, converts it to an array texture, and
+/// gives the entity a new [`SkyboxMaterial`] showing it, tinted with `color`. Any of the other
+/// [`SkyboxBundle`] components the entity is missing are added with their defaults, while the
+/// ones the scene already provides, such as the `Transform`, mesh, or pipelines, are kept. An
+/// empty `path` gives a skybox with only a color.
+///
+/// This is a minimal skybox entity in a scene file:
+///
+/// ```ron
+/// [
+///   (
+///     entity: 0,
+///     components: [
+///       {
+///         "type": "bevy_skybox_cubemap::scene::SkyboxSceneTexture",
+///         "struct": {
+///           "path": {
+///             "type": "alloc::string::String",
+///             "value": "labeled_skybox.png",
+///           },
+///           "color": {
+///             "type": "bevy_render::color::Color",
+///             "value": Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+///           },
+///         },
+///       },
+///     ],
+///   ),
+/// ]
+/// ```
+///
+/// Textures are converted whenever they are loaded while still stacked, so reloading a texture
+/// which was unloaded after the scene was despawned works, but the converted textures are kept
+/// loaded from then on.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct SkyboxSceneTexture {
+    /// Asset path of the texture, in the same `N` by `6*N` layout as for
+    /// [`SkyboxTextureConversion::make_array`].
+    pub path: String,
+    /// Color to tint the texture with, or the color of the sky if `path` is empty.
+    pub color: Color,
+}
+
+impl Default for SkyboxSceneTexture {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// System which turns entities with a newly added [`SkyboxSceneTexture`] into skyboxes.
+#[allow(clippy::type_complexity)]
+pub(crate) fn spawn_scene_skyboxes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut conversion: ResMut<SkyboxTextureConversion>,
+    skyboxes: Query<
+        (
+            Entity,
+            &SkyboxSceneTexture,
+            Option<&Handle<Mesh>>,
+            Option<&RenderPipelines>,
+            Option<&Visible>,
+            Option<&Transform>,
+        ),
+        Added<SkyboxSceneTexture>,
+    >,
+) {
+    for (entity, scene_texture, mesh, render_pipelines, visible, transform) in skyboxes.iter() {
+        let material = if scene_texture.path.is_empty() {
+            SkyboxMaterial::from_color(scene_texture.color)
+        } else {
+            let texture: Handle<Texture> = asset_server.load(scene_texture.path.as_str());
+            // The texture may already be loaded and converted for another skybox, in which case
+            // converting it again would break it. Tracking it only converts it while it's stacked.
+            if !conversion.tracked.contains(&texture) {
+                conversion.keep_converting(texture.clone());
+            }
+            SkyboxMaterial {
+                color: scene_texture.color,
+                ..SkyboxMaterial::from_texture(texture)
+            }
+        };
+
+        let mut bundle = SkyboxBundle::new(materials.add(material));
+        if let Some(mesh) = mesh {
+            bundle.mesh = mesh.clone();
+        }
+        if let Some(render_pipelines) = render_pipelines {
+            bundle.render_pipelines = render_pipelines.clone();
+        }
+        if let Some(visible) = visible {
+            bundle.visible = visible.clone();
+        }
+        if let Some(transform) = transform {
+            bundle.transform = *transform;
+        }
+        commands.entity(entity).insert_bundle(bundle);
+    }
+}
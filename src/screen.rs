@@ -0,0 +1,207 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finding which part of the sky is at a point on the screen.
+
+use bevy::prelude::*;
+use bevy::render::camera::{Camera, VisibleEntities};
+
+use crate::cube::FACES;
+use crate::{SkyboxCameraTint, SkyboxConvention, SkyboxMaterial};
+
+/// Finds the world-space direction of the sky shown at `position` in `window` by `camera`, such as
+/// the cursor position from [`Window::cursor_position`]. The direction is normalized, and only
+/// depends on the camera's rotation, not its position, since the sky is infinitely far away.
+///
+/// Returns `None` if `position` is outside the window or the camera's projection can't be inverted.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::render::camera::Camera;
+/// # use bevy_skybox_cubemap::screen_to_sky_direction;
+/// fn click_on_sky(
+///     windows: Res<Windows>,
+///     buttons: Res<Input<MouseButton>>,
+///     cameras: Query<(&Camera, &GlobalTransform)>,
+/// ) {
+///     let window = windows.get_primary().unwrap();
+///     if !buttons.just_pressed(MouseButton::Left) {
+///         return;
+///     }
+///     let cursor = match window.cursor_position() {
+///         Some(cursor) => cursor,
+///         None => return,
+///     };
+///     for (camera, transform) in cameras.iter() {
+///         if camera.window != window.id() {
+///             continue;
+///         }
+///         if let Some(direction) = screen_to_sky_direction(camera, transform, window, cursor) {
+///             info!("Clicked on the sky towards {:?}", direction);
+///         }
+///     }
+/// }
+/// ```
+pub fn screen_to_sky_direction(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    window: &Window,
+    position: Vec2,
+) -> Option<Vec3> {
+    let size = Vec2::new(window.width(), window.height());
+    if position.cmplt(Vec2::ZERO).any() || position.cmpgt(size).any() {
+        return None;
+    }
+    // Window positions start in the bottom left corner, like normalized device coordinates.
+    let ndc = position / size * 2.0 - Vec2::ONE;
+    let inverse_projection = camera.projection_matrix.inverse();
+    if !inverse_projection.is_finite() {
+        return None;
+    }
+    let ndc_to_world = camera_transform.compute_matrix() * inverse_projection;
+    // Unprojecting the near and far planes gives two points along the ray through the pixel, which
+    // works for both perspective and orthographic cameras.
+    let near = ndc_to_world.project_point3(ndc.extend(0.0));
+    let far = ndc_to_world.project_point3(ndc.extend(1.0));
+    let direction = (far - near).normalize();
+    if direction.is_finite() {
+        Some(direction)
+    } else {
+        None
+    }
+}
+
+/// Finds where the skybox drawn with `skybox_transform` and `material` samples its texture in the
+/// world-space `direction`, such as one from [`screen_to_sky_direction`], taking the skybox's
+/// rotation and the material's [`horizon_offset`](SkyboxMaterial::horizon_offset) and
+/// [`layer`](SkyboxMaterial::layer) into account.
+///
+/// Returns the texture layer and the texture coordinates within it. The layer is `6 * layer` plus
+/// the face, in the same order as the texture's layers. Texture coordinates are from `0.0` to
+/// `1.0`, starting in the top left corner of the face as it appears in the unconverted `N` by
+/// `6*N` image.
+///
+/// This assumes the texture is laid out in Bevy's own convention. For textures authored in another
+/// [`SkyboxConvention`], use [`SkyboxConvention::sky_texture_coordinates`].
+pub fn sky_texture_coordinates(
+    direction: Vec3,
+    skybox_transform: &GlobalTransform,
+    material: &SkyboxMaterial,
+) -> (u32, Vec2) {
+    SkyboxConvention::default().sky_texture_coordinates(direction, skybox_transform, material)
+}
+
+/// Component which opts a skybox in to being clicked on. When `button` is pressed over a camera
+/// which draws the skybox, the [`SkyboxPlugin`](crate::SkyboxPlugin) sends a [`SkyboxPicked`]
+/// event with where on the sky the cursor was.
+///
+/// Skyboxes are never hit by picking or raycasting plugins, since those only test entities that
+/// have their own components and the skybox doesn't add any of them. The event is sent for every
+/// click on the skybox, even when something in front of it was clicked on, so games which only
+/// want clicks on empty sky should ignore it when their picking plugin reports a hit. Clicks on
+/// faces left out with the material's [`hidden_faces`](SkyboxMaterial::hidden_faces) don't send
+/// the event, since the skybox isn't drawn there.
+#[derive(Debug, Clone)]
+pub struct SkyboxPickable {
+    /// Mouse button which picks the sky.
+    pub button: MouseButton,
+}
+
+impl Default for SkyboxPickable {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+        }
+    }
+}
+
+/// Event sent when a [`SkyboxPickable`] skybox is clicked on.
+#[derive(Debug, Clone)]
+pub struct SkyboxPicked {
+    /// Skybox entity which was clicked on.
+    pub skybox: Entity,
+    /// Camera entity the skybox was clicked on in.
+    pub camera: Entity,
+    /// World-space direction of the click, from [`screen_to_sky_direction`].
+    pub direction: Vec3,
+    /// Texture layer the skybox shows in that direction, from [`sky_texture_coordinates`]. If the
+    /// camera has a [`SkyboxCameraTint`] with a `layer`, this is in the camera's sky rather than the
+    /// material's.
+    pub layer: u32,
+    /// Texture coordinates within `layer`, from [`sky_texture_coordinates`].
+    pub uv: Vec2,
+}
+
+/// System which sends [`SkyboxPicked`] events.
+pub(crate) fn pick_skyboxes(
+    windows: Res<Windows>,
+    buttons: Res<Input<MouseButton>>,
+    convention: Res<SkyboxConvention>,
+    materials: Res<Assets<SkyboxMaterial>>,
+    mut events: EventWriter<SkyboxPicked>,
+    cameras: Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        &VisibleEntities,
+        Option<&SkyboxCameraTint>,
+    )>,
+    skyboxes: Query<(&SkyboxPickable, &GlobalTransform, &Handle<SkyboxMaterial>)>,
+) {
+    for (camera_entity, camera, camera_transform, visible_entities, tint) in cameras.iter() {
+        let window = match windows.get(camera.window) {
+            Some(window) => window,
+            None => continue,
+        };
+        let cursor = match window.cursor_position() {
+            Some(cursor) => cursor,
+            None => continue,
+        };
+        let direction = match screen_to_sky_direction(camera, camera_transform, window, cursor) {
+            Some(direction) => direction,
+            None => continue,
+        };
+        // Going through the camera's visible entities respects render layers and visibility.
+        for visible in visible_entities.iter() {
+            let (pickable, transform, handle) = match skyboxes.get(visible.entity) {
+                Ok(skybox) => skybox,
+                Err(_) => continue,
+            };
+            if !buttons.just_pressed(pickable.button) {
+                continue;
+            }
+            let material = match materials.get(handle) {
+                Some(material) => material,
+                None => continue,
+            };
+            let (mut layer, uv) =
+                convention.sky_texture_coordinates(direction, transform, material);
+            // Nothing of the skybox is drawn on hidden faces.
+            if (material.hidden_faces >> (layer % FACES as u32)) & 1 != 0 {
+                continue;
+            }
+            // The camera may show a different sky of the texture than the material does.
+            if let Some(sky) = tint.and_then(|tint| tint.layer) {
+                layer = sky * FACES as u32 + layer % FACES as u32;
+            }
+            events.send(SkyboxPicked {
+                skybox: visible.entity,
+                camera: camera_entity,
+                direction,
+                layer,
+                uv,
+            });
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Switching all skyboxes on and off.
+
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+use bevy::render::draw::OutsideFrustum;
+
+use crate::SkyboxMaterial;
+
+/// Resource with settings for every skybox, added by the [`SkyboxPlugin`](crate::SkyboxPlugin).
+///
+/// Setting `enabled` to `false` stops drawing skyboxes and pauses the systems which animate them,
+/// such as [`SkyboxRotation`](crate::SkyboxRotation) and
+/// [`SkyboxTransition`](crate::SkyboxTransition), without despawning anything. This is useful for
+/// benchmarking, a "no sky" graphics option, or sequences which only take place indoors. Skyboxes
+/// pick up where they left off when it is set back to `true`.
+///
+/// Skyboxes are hidden by giving them Bevy's `OutsideFrustum` marker while they're disabled, rather
+/// than by changing their [`Visible`], so skyboxes which were hidden before stay hidden afterwards.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyboxSettings;
+/// fn toggle_sky(keys: Res<Input<KeyCode>>, mut settings: ResMut<SkyboxSettings>) {
+///     if keys.just_pressed(KeyCode::F2) {
+///         settings.enabled = !settings.enabled;
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxSettings {
+    /// Whether skyboxes are drawn and animated.
+    pub enabled: bool,
+}
+
+impl Default for SkyboxSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Run criteria for systems which only need to run while skyboxes are enabled.
+pub(crate) fn skyboxes_enabled(settings: Res<SkyboxSettings>) -> ShouldRun {
+    if settings.enabled {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// System which hides and shows skyboxes when [`SkyboxSettings::enabled`] changes, and hides
+/// skyboxes spawned while they're disabled.
+pub(crate) fn apply_skybox_settings(
+    mut commands: Commands,
+    settings: Res<SkyboxSettings>,
+    skyboxes: Query<Entity, With<Handle<SkyboxMaterial>>>,
+    new_skyboxes: Query<Entity, Added<Handle<SkyboxMaterial>>>,
+) {
+    if settings.is_changed() {
+        for entity in skyboxes.iter() {
+            if settings.enabled {
+                commands.entity(entity).remove::<OutsideFrustum>();
+            } else {
+                commands.entity(entity).insert(OutsideFrustum);
+            }
+        }
+    } else if !settings.enabled {
+        for entity in new_skyboxes.iter() {
+            commands.entity(entity).insert(OutsideFrustum);
+        }
+    }
+}
@@ -14,7 +14,24 @@
 
 #version 450
 layout(location = 0) in vec3 TexCoords;
+layout(location = 1) in vec3 WorldDirection;
+layout(location = 2) in vec3 LocalUp;
 
+// Set for each camera from its `SkyboxCameraTint`. Must match `SkyboxCameraTint::uniform`.
+layout(set = 0, binding = 2) uniform SkyboxCamera {
+    // Multiplied with everything the camera sees of the sky.
+    vec4 cameraTint;
+    // Color to use instead of the material's color.
+    vec4 cameraColor;
+    // x: 1.0 to use cameraColor instead of the material's color, y: 1.0 to show the sky at z
+    // instead of the material's layer.
+    vec4 cameraFlags;
+    // Columns of the matrix taking directions in the skybox's space to directions in its textures,
+    // from the `SkyboxConvention`.
+    vec4 conventionX;
+    vec4 conventionY;
+    vec4 conventionZ;
+};
 layout(set = 2, binding = 0) uniform SkyboxMaterial_color {
     vec4 color;
 };
@@ -22,9 +39,122 @@ layout(set = 2, binding = 0) uniform SkyboxMaterial_color {
 layout(set = 2, binding = 1) uniform texture2DArray SkyboxMaterial_texture;
 layout(set = 2, binding = 2) uniform sampler SkyboxMaterial_texture_sampler;
 #endif
+// Scalar parameters of the material, packed into vec4s. Must match `SkyboxMaterial::params`.
+layout(set = 2, binding = 3) uniform SkyboxMaterial_params {
+    // x: blend factor, y: 1.0 to use blendWeights instead of the blend factor, z: index of the sky
+    // to sample from the textures, w: bitmask of faces to hide.
+    vec4 blend;
+    // xyz: direction of the lightning flash, w: cosine of the flash's angular radius.
+    vec4 flashDirection;
+    // rgb: color of the flash, premultiplied by its intensity.
+    vec4 flashColor;
+    // xyz: direction of the head of the meteor, w: angular half-width of the meteor's streak.
+    vec4 meteorHead;
+    // xyz: direction of the end of the meteor's trail.
+    vec4 meteorTail;
+    // rgb: color of the meteor, premultiplied by its brightness.
+    vec4 meteorColor;
+    // rgb: color of the horizon haze, a: strength of the haze.
+    vec4 hazeColor;
+    // x: angle from the horizon where the haze starts to fade, y: angle it fades out over.
+    vec4 hazeBand;
+    // Weights of texture, blend_texture, blend_texture_2, and blend_texture_3.
+    vec4 blendWeights;
+    // xyz: direction of the sun, w: angular radius of the sun.
+    vec4 sunDisc;
+    // rgb: color of the sun.
+    vec4 sunColor;
+    // xyz: direction of the moon, w: angular radius of the moon.
+    vec4 moonDisc;
+    // rgb: color of the moon, a: how much the moon hides the sky behind it.
+    vec4 moonColor;
+    // rgb: color of the sun's corona.
+    vec4 coronaColor;
+    // rgb: color of the ground below the horizon, a: strength of the ground.
+    vec4 groundColor;
+    // x: angle below the horizon over which the sky fades into the ground.
+    vec4 groundBand;
+    // Tints of each face, in layer order.
+    vec4 faceTint0;
+    vec4 faceTint1;
+    vec4 faceTint2;
+    vec4 faceTint3;
+    vec4 faceTint4;
+    vec4 faceTint5;
+    // x: offset added to the vertical component of directions before sampling the sky.
+    vec4 horizon;
+    // rgb: color to multiply the sky textures by for their color temperature, w: angle in radians
+    // to rotate the hue of the sky textures by.
+    vec4 whiteBalance;
+    // rgb: color to fade the whole sky to, a: how far to fade it.
+    vec4 fadeColor;
+    // x: 1.0 to draw the sky in night vision, y: night-vision gain, z: strength of the noise, w:
+    // seed for the noise.
+    vec4 nightVision;
+    // rgb: color of the night-vision image.
+    vec4 nightVisionColor;
+    // x: refractive index of the water the camera is under, or 0.0 above water, y: angle in radians
+    // over which the edge of Snell's window fades out.
+    vec4 underwater;
+    // rgb: color multiplied with the sky seen through Snell's window.
+    vec4 underwaterTint;
+    // rgb: color of the water outside Snell's window.
+    vec4 underwaterColor;
+    // x: angle around the horizon over which the sky blends into the lower texture.
+    vec4 lowerBand;
+    // rgb: color of the glow along the horizon around the sun's bearing.
+    vec4 horizonGlowColor;
+    // x: angle around the sun's bearing the glow fades out over, y: angle from the horizon it fades
+    // out over.
+    vec4 horizonGlow;
+    // rgb: color of the light pollution, premultiplied by its intensity, w: angle above the horizon
+    // it fades out over.
+    vec4 lightPollution;
+    // x: how much of the light pollution comes from cities, y: angle to either side of each city
+    // its glow fades out over, z: number of cities.
+    vec4 lightPollutionCities;
+    // Compass bearings of up to four cities, clockwise from -Z towards +X.
+    vec4 cityAzimuths;
+    // rgb: color of the rain or snow streaks, a: how strongly they cover the sky.
+    vec4 precipitationColor;
+    // xyz: direction the streaks fall in, w: fraction of the sky they fall over.
+    vec4 precipitationDirection;
+    // x: how far the streaks have scrolled, y: fraction of each streak's path it covers.
+    vec4 precipitationStreaks;
+    // Planets and other small bodies. For each disc, xyz: direction of the disc, w: cosine of its
+    // angular radius, followed by rgb: color of the disc. Unused discs are black. These are separate
+    // members rather than an array because Bevy's shader reflection only counts the first element
+    // of uniform arrays when sizing bindings.
+    vec4 disc0;
+    vec4 disc0Color;
+    vec4 disc1;
+    vec4 disc1Color;
+    vec4 disc2;
+    vec4 disc2Color;
+    vec4 disc3;
+    vec4 disc3Color;
+};
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE
+layout(set = 2, binding = 4) uniform texture2DArray SkyboxMaterial_blend_texture;
+layout(set = 2, binding = 5) uniform sampler SkyboxMaterial_blend_texture_sampler;
+#endif
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE_2
+layout(set = 2, binding = 6) uniform texture2DArray SkyboxMaterial_blend_texture_2;
+layout(set = 2, binding = 7) uniform sampler SkyboxMaterial_blend_texture_2_sampler;
+#endif
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE_3
+layout(set = 2, binding = 8) uniform texture2DArray SkyboxMaterial_blend_texture_3;
+layout(set = 2, binding = 9) uniform sampler SkyboxMaterial_blend_texture_3_sampler;
+#endif
+#ifdef SKYBOXMATERIAL_LOWER_TEXTURE
+layout(set = 2, binding = 10) uniform texture2DArray SkyboxMaterial_lower_texture;
+layout(set = 2, binding = 11) uniform sampler SkyboxMaterial_lower_texture_sampler;
+#endif
 
 layout(location = 0) out vec4 o_Target;
 
+const float PI = 3.14159265358979;
+
 // This is a handwritten cubemap sampler. We should use the shader language's builtin cubemap
 // sampling, but that doesn't work right now because Bevy has a bug binding cubemaps currently.
 // So instead we just write our own cubemap sampler and use an array texture, which does work
@@ -51,20 +181,283 @@ vec3 sampleCubeHacky(const vec3 ray) {
     return vec3(uv * maxAdjust + 0.5, faceIndex);
 }
 
+// Draws a small disc with an antialiased edge, for planets and other bodies.
+vec3 skyDisc(const vec3 ray, const vec4 disc, const vec3 discColor) {
+    float center = dot(ray, disc.xyz);
+    float edge = fwidth(center);
+    return discColor * smoothstep(disc.w - edge, disc.w + edge, center);
+}
+
+// SKYBOX_EXTENSION_SOURCE
+// `SkyboxExtensionPlugin` replaces the line above with the source of a `SkyboxMaterialExtension`,
+// which defines `skyboxExtension`, and defines SKYBOX_EXTENSION.
+
+// Angle in radians between two unit vectors.
+float angleBetween(const vec3 a, const vec3 b) {
+    return atan(length(cross(a, b)), dot(a, b));
+}
+
+// Rotates the hue of `color` by `angle` radians, by turning it around the gray axis of the RGB
+// cube.
+vec3 rotateHue(const vec3 color, const float angle) {
+    const vec3 gray = vec3(0.57735026);
+    float c = cos(angle);
+    vec3 rotated = color * c + cross(gray, color) * sin(angle)
+        + gray * dot(gray, color) * (1.0 - c);
+    return max(rotated, vec3(0.0));
+}
+
+// How much of a pixel `angle` radians from the center of a disc is covered by it, with an
+// antialiased edge.
+float discCoverage(const float angle, const float radius) {
+    if (radius <= 0.0) {
+        return 0.0;
+    }
+    float edge = fwidth(angle);
+    return 1.0 - smoothstep(radius - edge, radius + edge, angle);
+}
+
+// Pseudo-random number from 0.0 to 1.0 for `n`.
+float hash(const float n) {
+    return fract(sin(n * 12.9898) * 43758.5453);
+}
+
+// Turns `ray` towards or away from `up` so that it is `angle` radians from it, keeping its azimuth.
+vec3 tiltFromUp(const vec3 ray, const vec3 up, const float angle) {
+    vec3 across = ray - up * dot(ray, up);
+    float acrossLength = length(across);
+    if (acrossLength < 0.000001) {
+        return up;
+    }
+    return up * cos(angle) + across / acrossLength * sin(angle);
+}
+
 void main() {
+    vec3 localRay = normalize(TexCoords);
+    vec3 worldRay = normalize(WorldDirection);
+    // Underwater, the whole sky above the surface is refracted into Snell's window, a cone around
+    // straight up, and only water is seen outside it.
+    float water = 0.0;
+    if (underwater.x > 0.0) {
+        float windowAngle = asin(1.0 / max(underwater.x, 1.0));
+        float fromZenith = acos(clamp(worldRay.y, -1.0, 1.0));
+        water = smoothstep(windowAngle - underwater.y, windowAngle, fromZenith);
+        float airAngle = asin(min(underwater.x * sin(min(fromZenith, windowAngle)), 1.0));
+        localRay = tiltFromUp(localRay, normalize(LocalUp), airAngle);
+        worldRay = tiltFromUp(worldRay, vec3(0.0, 1.0, 0.0), airAngle);
+    }
+
+    // Directions to sample the sky texture in, with the horizon shifted.
+    vec3 skyRay = mat3(conventionX.xyz, conventionY.xyz, conventionZ.xyz)
+        * (localRay + vec3(0.0, horizon.x, 0.0));
+    vec3 uvIndex = sampleCubeHacky(skyRay);
+    int face = int(uvIndex.z);
+    // Elevation of the ray above the horizon, which is level in world space.
+    float signedElevation = asin(clamp(worldRay.y + horizon.x, -1.0, 1.0));
+    if (((int(blend.w) >> face) & 1) != 0) {
+        discard;
+    }
+
+#if defined(SKYBOXMATERIAL_TEXTURE) || defined(SKYBOXMATERIAL_BLEND_TEXTURE) \
+    || defined(SKYBOXMATERIAL_BLEND_TEXTURE_2) || defined(SKYBOXMATERIAL_BLEND_TEXTURE_3) \
+    || defined(SKYBOXMATERIAL_LOWER_TEXTURE)
+    uvIndex.z += 6.0 * (cameraFlags.y > 0.5 ? cameraFlags.z : blend.z);
+#endif
+
 #ifdef SKYBOXMATERIAL_TEXTURE
-    vec3 uvIndex = sampleCubeHacky(TexCoords);
-    o_Target = texture(
+    vec4 skyColor = texture(
         sampler2DArray(SkyboxMaterial_texture, SkyboxMaterial_texture_sampler),
         uvIndex
-    ) * color;
+    );
 
     // This is how this should work.
-    // o_Target = texture(
+    // vec4 skyColor = texture(
     //     samplerCube(SkyboxMaterial_texture, SkyboxMaterial_texture_sampler),
     //     TexCoords
-    // ) * color;
+    // );
+#else
+    vec4 skyColor = vec4(1.0);
+#endif
+
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE
+    vec4 blendColor = texture(
+        sampler2DArray(SkyboxMaterial_blend_texture, SkyboxMaterial_blend_texture_sampler),
+        uvIndex
+    );
+#else
+    vec4 blendColor = vec4(1.0);
+#endif
+
+    if (blend.y > 0.5) {
+        // Weighted blend of up to four textures. Missing textures count as plain white, just like
+        // a missing main texture.
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE_2
+        vec4 blendColor2 = texture(
+            sampler2DArray(SkyboxMaterial_blend_texture_2, SkyboxMaterial_blend_texture_2_sampler),
+            uvIndex
+        );
+#else
+        vec4 blendColor2 = vec4(1.0);
+#endif
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE_3
+        vec4 blendColor3 = texture(
+            sampler2DArray(SkyboxMaterial_blend_texture_3, SkyboxMaterial_blend_texture_3_sampler),
+            uvIndex
+        );
 #else
-    o_Target = color;
+        vec4 blendColor3 = vec4(1.0);
+#endif
+        vec4 weights = max(blendWeights, vec4(0.0));
+        float totalWeight = dot(weights, vec4(1.0));
+        if (totalWeight > 0.0) {
+            skyColor = (skyColor * weights.x + blendColor * weights.y + blendColor2 * weights.z
+                + blendColor3 * weights.w) / totalWeight;
+        }
+    } else {
+#ifdef SKYBOXMATERIAL_BLEND_TEXTURE
+        skyColor = mix(skyColor, blendColor, clamp(blend.x, 0.0, 1.0));
+#endif
+    }
+
+#ifdef SKYBOXMATERIAL_LOWER_TEXTURE
+    // Below the horizon, the lower texture takes over from the rest of the sky.
+    vec4 lowerColor = texture(
+        sampler2DArray(SkyboxMaterial_lower_texture, SkyboxMaterial_lower_texture_sampler),
+        uvIndex
+    );
+    float lowerSoftness = max(lowerBand.x, 0.0001) * 0.5;
+    skyColor = mix(skyColor, lowerColor, smoothstep(lowerSoftness, -lowerSoftness,
+        signedElevation));
+#endif
+
+    if (whiteBalance.w != 0.0) {
+        skyColor.rgb = rotateHue(skyColor.rgb, whiteBalance.w);
+    }
+
+    vec4 faceTints[6] = vec4[6](faceTint0, faceTint1, faceTint2, faceTint3, faceTint4, faceTint5);
+    vec4 baseColor = cameraFlags.x > 0.5 ? cameraColor : color;
+    o_Target = skyColor * vec4(whiteBalance.rgb, 1.0) * baseColor * faceTints[face];
+
+    // Lightning flashes brighten the sky around their direction, fading out towards the edge of
+    // their spread.
+    float flashCenter = dot(worldRay, flashDirection.xyz);
+    o_Target.rgb += flashColor.rgb * smoothstep(flashDirection.w, 1.0, flashCenter);
+
+    vec3 discRay = worldRay;
+
+    // The sun and its corona, with the moon in front of them. Angles are measured with atan rather
+    // than by comparing dot products against a cosine, which loses too much precision at the size
+    // of the sun and moon to draw the edge of an eclipse.
+    float sunAngle = angleBetween(discRay, sunDisc.xyz);
+    float sun = discCoverage(sunAngle, sunDisc.w);
+    float corona = exp(-3.0 * max(sunAngle - sunDisc.w, 0.0) / max(sunDisc.w, 0.0001));
+    float moon = discCoverage(angleBetween(discRay, moonDisc.xyz), moonDisc.w);
+    o_Target.rgb += (sunColor.rgb * sun + coronaColor.rgb * corona) * (1.0 - moon);
+    o_Target.rgb = o_Target.rgb * (1.0 - moon * moonColor.a) + moonColor.rgb * moon;
+
+    o_Target.rgb += skyDisc(discRay, disc0, disc0Color.rgb);
+    o_Target.rgb += skyDisc(discRay, disc1, disc1Color.rgb);
+    o_Target.rgb += skyDisc(discRay, disc2, disc2Color.rgb);
+    o_Target.rgb += skyDisc(discRay, disc3, disc3Color.rgb);
+
+    // Meteors are drawn as a thin streak along the great circle from the tail to the head, getting
+    // brighter towards the head.
+    if (any(greaterThan(meteorColor.rgb, vec3(0.0)))) {
+        vec3 ray = worldRay;
+        vec3 meteorNormal = cross(meteorTail.xyz, meteorHead.xyz);
+        float trailSin = length(meteorNormal);
+        if (trailSin > 0.0) {
+            meteorNormal /= trailSin;
+            // Distance from the streak's great circle, and how far along the trail the ray is.
+            float offset = abs(dot(ray, meteorNormal));
+            vec3 onCircle = ray - meteorNormal * dot(ray, meteorNormal);
+            float along = atan(dot(cross(meteorTail.xyz, onCircle), meteorNormal),
+                dot(meteorTail.xyz, onCircle));
+            float trail = along / atan(trailSin, dot(meteorTail.xyz, meteorHead.xyz));
+            float streak = step(0.0, trail) * step(trail, 1.0) * trail * trail;
+            o_Target.rgb += meteorColor.rgb * streak * (1.0 - smoothstep(0.0, meteorHead.w, offset));
+        }
+    }
+
+    // City lights glow over the horizon, brightest towards the cities themselves.
+    if (any(greaterThan(lightPollution.rgb, vec3(0.0)))) {
+        float glow = 1.0;
+        int cities = int(lightPollutionCities.z);
+        if (cities > 0 && dot(worldRay.xz, worldRay.xz) > 0.0) {
+            float azimuth = atan(worldRay.x, -worldRay.z);
+            float city = 0.0;
+            for (int i = 0; i < cities; i++) {
+                float bearing = abs(mod(azimuth - cityAzimuths[i] + PI, 2.0 * PI) - PI)
+                    / max(lightPollutionCities.y, 0.0001);
+                city = max(city, exp(-bearing * bearing));
+            }
+            glow = mix(1.0, city, lightPollutionCities.x);
+        }
+        glow *= exp(-max(signedElevation, 0.0) / max(lightPollution.w, 0.0001));
+        o_Target.rgb += lightPollution.rgb * glow;
+    }
+
+    // Below the horizon, the sky fades into the ground.
+    float ground = smoothstep(0.0, max(groundBand.x, 0.0001), -signedElevation);
+    o_Target.rgb = mix(o_Target.rgb, groundColor.rgb, ground * groundColor.a);
+
+    // Haze fades everything towards a single color near the horizon.
+    float elevation = abs(signedElevation);
+    float haze = 1.0 - smoothstep(hazeBand.x, hazeBand.x + max(hazeBand.y, 0.0001), elevation);
+    o_Target.rgb = mix(o_Target.rgb, hazeColor.rgb, haze * hazeColor.a);
+
+    // The horizon glows brighter towards the sun's bearing, like the sky at sunrise and sunset.
+    if (any(greaterThan(horizonGlowColor.rgb, vec3(0.0)))) {
+        vec2 rayBearing = worldRay.xz;
+        vec2 sunBearing = sunDisc.xz;
+        if (dot(rayBearing, rayBearing) > 0.0 && dot(sunBearing, sunBearing) > 0.0) {
+            float bearing = acos(clamp(dot(normalize(rayBearing), normalize(sunBearing)), -1.0,
+                1.0)) / max(horizonGlow.x, 0.0001);
+            float glow = exp(-bearing * bearing) * exp(-elevation / max(horizonGlow.y, 0.0001));
+            o_Target.rgb += horizonGlowColor.rgb * glow;
+        }
+    }
+
+    // Rain and snow fall in streaks along great circles towards the point they're falling towards,
+    // scattered into columns around it.
+    if (precipitationColor.a > 0.0 && precipitationDirection.w > 0.0) {
+        vec3 fall = precipitationDirection.xyz;
+        vec3 across = normalize(cross(fall, abs(fall.y) < 0.99 ? vec3(0.0, 1.0, 0.0)
+            : vec3(1.0, 0.0, 0.0)));
+        float along = acos(clamp(dot(worldRay, fall), -1.0, 1.0));
+        float around = (atan(dot(worldRay, cross(fall, across)), dot(worldRay, across)) + PI)
+            / (2.0 * PI) * 400.0;
+        float column = floor(around);
+        float phase = fract(along * 4.0 + precipitationStreaks.x + hash(column + 0.5));
+        float width = (fract(around) - 0.5) / 0.15;
+        float streak = step(hash(column + 0.25), precipitationDirection.w)
+            * step(phase, precipitationStreaks.y) * exp(-width * width)
+            // The columns bunch up where the streaks meet, so fade them out there.
+            * sin(along);
+        o_Target.rgb = mix(o_Target.rgb, precipitationColor.rgb, streak * precipitationColor.a);
+    }
+
+#ifdef SKYBOX_EXTENSION
+    o_Target = skyboxExtension(o_Target, worldRay);
 #endif
+
+    if (underwater.x > 0.0) {
+        o_Target.rgb = mix(o_Target.rgb * underwaterTint.rgb, underwaterColor.rgb, water);
+    }
+
+    // Night vision amplifies the brightness of everything drawn so far into a single color, with
+    // noise like an image intensifier's.
+    if (nightVision.x > 0.5) {
+        float brightness = dot(o_Target.rgb, vec3(0.2126, 0.7152, 0.0722)) * nightVision.y;
+        float grain = fract(sin(dot(gl_FragCoord.xy + nightVision.w * vec2(17.0, 59.0),
+            vec2(12.9898, 78.233))) * 43758.5453);
+        brightness += (grain - 0.5) * 2.0 * nightVision.z;
+        o_Target.rgb = nightVisionColor.rgb * clamp(brightness, 0.0, 1.0);
+    }
+
+    // Fading to a color covers everything, including extensions, like a fade done in post.
+    o_Target.rgb = mix(o_Target.rgb, fadeColor.rgb, fadeColor.a);
+
+    // The camera's tint goes on last, like a filter over the lens.
+    o_Target *= cameraTint;
 }
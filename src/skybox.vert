@@ -26,6 +26,8 @@ layout(set = 1, binding = 0) uniform Transform {
 };
 
 layout(location = 0) out vec3 TexCoords;
+layout(location = 1) out vec3 WorldDirection;
+layout(location = 2) out vec3 LocalUp;
 
 void main() {
     // ViewProj is Proj * inverse(View). We want to get Proj * inverse(untranslatedView). However,
@@ -50,4 +52,10 @@ void main() {
 
     // Since we're sampling a cubemap, texcoords is just the vertex coordinate.
     TexCoords = Vertex_Position;
+    // Effects that are positioned in the world rather than on the sky texture, like lightning
+    // flashes, need the direction after the skybox's rotation is applied.
+    WorldDirection = mat3(untranslatedModel) * Vertex_Position;
+    // Underwater refraction bends rays towards the world's up direction, but the sky texture is
+    // sampled in the skybox's own space. The inverse of a rotation is its transpose.
+    LocalUp = transpose(mat3(untranslatedModel)) * vec3(0.0, 1.0, 0.0);
 }
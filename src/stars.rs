@@ -0,0 +1,337 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Baking night sky textures from star catalogs.
+
+use bevy::prelude::*;
+
+use crate::cube::CubeImage;
+use crate::random::Rng;
+
+/// A star from a star catalog, to be baked into a skybox with [`StarCubemap`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Star {
+    /// Right ascension in hours, from `0.0` to `24.0`.
+    pub right_ascension: f32,
+    /// Declination in degrees, from `-90.0` (south celestial pole) to `90.0` (north celestial
+    /// pole).
+    pub declination: f32,
+    /// Apparent visual magnitude. Smaller is brighter.
+    pub magnitude: f32,
+    /// B-V color index, which determines the star's color. `0.0` is white, negative values are
+    /// blue, and positive values are orange and red. The sun is about `0.65`.
+    pub color_index: f32,
+}
+
+impl Star {
+    /// Unit vector towards the star in equatorial coordinates: X towards the vernal equinox, Z
+    /// towards the north celestial pole.
+    pub fn direction(&self) -> Vec3 {
+        let right_ascension = (self.right_ascension * 15.0).to_radians();
+        let declination = self.declination.to_radians();
+        let (sin_ra, cos_ra) = right_ascension.sin_cos();
+        let (sin_dec, cos_dec) = declination.sin_cos();
+        Vec3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec)
+    }
+
+    /// Approximate color of the star, based on its color index.
+    pub fn color(&self) -> Color {
+        // Estimate the temperature from the color index (Ballesteros 2012).
+        let bv = self.color_index.clamp(-0.4, 2.0);
+        temperature_color(4600.0 * (1.0 / (0.92 * bv + 1.7) + 1.0 / (0.92 * bv + 0.62)))
+    }
+
+    /// Parses stars from a CSV file in the format of the [HYG
+    /// database](https://github.com/astronexus/HYG-Database). Only the `ra`, `dec`, `mag`, and `ci`
+    /// columns are used, and rows which can't be parsed are skipped. Since HYG includes the sun, it
+    /// is skipped too.
+    ///
+    /// The catalog can be embedded in your game with [`include_str!`], or read in at runtime.
+    pub fn parse_hyg_csv(csv: &str) -> Vec<Star> {
+        let mut lines = csv.lines();
+        let header: Vec<_> = match lines.next() {
+            Some(header) => header
+                .split(',')
+                .map(|h| h.trim().trim_matches('"'))
+                .collect(),
+            None => return Vec::new(),
+        };
+        let column = |name| header.iter().position(|h| *h == name);
+        let (ra, dec, mag) = match (column("ra"), column("dec"), column("mag")) {
+            (Some(ra), Some(dec), Some(mag)) => (ra, dec, mag),
+            _ => return Vec::new(),
+        };
+        let ci = column("ci");
+
+        lines
+            .filter_map(|line| {
+                let fields: Vec<_> = line
+                    .split(',')
+                    .map(|f| f.trim().trim_matches('"'))
+                    .collect();
+                let field = |index: usize| fields.get(index)?.parse::<f32>().ok();
+                let star = Star {
+                    right_ascension: field(ra)?,
+                    declination: field(dec)?,
+                    magnitude: field(mag)?,
+                    // Some stars have no measured color index, so just make them white.
+                    color_index: ci.and_then(field).unwrap_or(0.0),
+                };
+                // Nothing else in the sky is anywhere near as bright as the sun.
+                if star.magnitude < -20.0 {
+                    None
+                } else {
+                    Some(star)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Approximate color of a black body at `temperature` in Kelvin, with the brightest channel at
+/// full brightness. This is a fit of the black body curve which is accurate enough for tinting, not
+/// a physical model.
+pub(crate) fn temperature_color(temperature: f32) -> Color {
+    let t = temperature / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (t - 60.0).powf(-0.13320476)
+    };
+    let green = if t <= 66.0 {
+        99.4708 * t.ln() - 161.11957
+    } else {
+        288.12217 * (t - 60.0).powf(-0.07551485)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (t - 10.0).ln() - 305.0448
+    };
+    let channel = |value: f32| value.clamp(0.0, 255.0) / 255.0;
+    Color::rgb(channel(red), channel(green), channel(blue))
+}
+
+/// Settings for generating random stars to bake with a [`StarCubemap`], for night skies which
+/// don't need to match the real one, such as for other worlds.
+///
+/// Stars are spread evenly over the sky. Their magnitudes are picked so that fainter stars are
+/// more common, with about three times as many stars for each magnitude fainter, like the real
+/// sky. The same settings always generate the same stars.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{RandomStars, StarCubemap};
+/// # fn setup(mut textures: ResMut<Assets<Texture>>, world_seed: u64) {
+/// let stars = RandomStars {
+///     seed: world_seed,
+///     ..Default::default()
+/// }
+/// .generate();
+/// let texture = textures.add(StarCubemap::default().bake(&stars));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RandomStars {
+    /// Seed for the random number generator.
+    pub seed: u64,
+    /// Number of stars to generate.
+    pub count: u32,
+    /// Range of magnitudes of the stars, from the brightest to the faintest.
+    pub magnitude: (f32, f32),
+    /// Range of B-V color indices of the stars; see [`Star::color_index`]. `(-0.3, 1.6)` covers
+    /// blue-white to red stars.
+    pub color_index: (f32, f32),
+}
+
+impl RandomStars {
+    /// How much more common stars are for each magnitude fainter, as a power of ten.
+    const MAGNITUDE_GROWTH: f32 = 0.5;
+
+    /// Generates the stars.
+    pub fn generate(&self) -> Vec<Star> {
+        let mut rng = Rng::new(self.seed);
+        let (brightest, faintest) = self.magnitude;
+        // Inverse of the share of stars brighter than each magnitude, which grows exponentially.
+        let range = 10f32.powf(Self::MAGNITUDE_GROWTH * (faintest - brightest)) - 1.0;
+        (0..self.count)
+            .map(|_| {
+                let right_ascension = rng.range(0.0, 24.0);
+                // Picking the sine of the declination uniformly spreads the stars evenly over the sky.
+                let declination = rng.range(-1.0, 1.0).asin().to_degrees();
+                let magnitude =
+                    brightest + (1.0 + rng.next_f32() * range).log10() / Self::MAGNITUDE_GROWTH;
+                let color_index = rng.range(self.color_index.0, self.color_index.1);
+                Star {
+                    right_ascension,
+                    declination,
+                    magnitude,
+                    color_index,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for RandomStars {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            count: 5000,
+            magnitude: (-1.0, 6.5),
+            color_index: (-0.3, 1.6),
+        }
+    }
+}
+
+/// Settings for baking a night sky texture from a star catalog.
+///
+/// Stars are placed by their equatorial coordinates, rotated by `orientation`. To make the stars
+/// appear where they really are in the sky at some place and time, either bake them with an
+/// `orientation` from [`astronomy::sky_rotation`](crate::astronomy::sky_rotation), or bake them
+/// with the default orientation and set the skybox's rotation to it, which lets the stars turn as
+/// time passes. [`bake`](Self::bake) returns a 6-layer array texture.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, Star, StarCubemap};
+/// fn setup(
+///     mut commands: Commands,
+///     mut textures: ResMut<Assets<Texture>>,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+/// ) {
+///     let catalog = std::fs::read_to_string("assets/hygdata_v3.csv").unwrap();
+///     let stars = Star::parse_hyg_csv(&catalog);
+///     let texture = textures.add(StarCubemap::default().bake(&stars));
+///     commands.spawn_bundle(SkyboxBundle::new(
+///         skyboxes.add(SkyboxMaterial::from_texture(texture)),
+///     ));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StarCubemap {
+    /// Width and height of each face of the texture, in pixels.
+    pub size: u32,
+    /// Stars fainter than this magnitude are left out. About `6.5` is the limit of what can be
+    /// seen with the naked eye under a dark sky.
+    pub limiting_magnitude: f32,
+    /// Magnitude of a star which fills one pixel at full brightness. Stars brighter than this are
+    /// spread out over neighboring pixels, and fainter stars are dimmer.
+    pub reference_magnitude: f32,
+    /// Rotation from equatorial coordinates to the directions of the skybox.
+    pub orientation: Quat,
+    /// Color of the sky behind the stars.
+    pub background: Color,
+}
+
+impl StarCubemap {
+    /// Bakes `stars` into a skybox texture.
+    pub fn bake(&self, stars: &[Star]) -> Texture {
+        let background = Vec4::from(self.background.as_linear_rgba_f32());
+        let mut image = CubeImage::new(self.size, background);
+        for star in stars {
+            if star.magnitude > self.limiting_magnitude {
+                continue;
+            }
+            // Each step of 5 magnitudes is a factor of 100 in brightness.
+            let flux = 10f32.powf(-0.4 * (star.magnitude - self.reference_magnitude));
+            let color = Vec4::from(star.color().as_linear_rgba_f32()).truncate() * flux;
+            let direction = self.orientation * star.direction();
+            if flux <= 1.0 {
+                image.splat(direction, color.extend(0.0));
+            } else {
+                // Keep very bright stars from all clipping to the same single white pixel by
+                // spreading them into a small disc.
+                let radius = flux.sqrt() / self.size as f32;
+                let tangent = direction.any_orthonormal_vector();
+                let bitangent = direction.cross(tangent);
+                const SAMPLES: usize = 7;
+                image.splat(direction, (color / flux).extend(0.0));
+                let ring = (color - color / flux) / SAMPLES as f32;
+                for i in 0..SAMPLES {
+                    let angle = i as f32 / SAMPLES as f32 * std::f32::consts::TAU;
+                    let offset = (tangent * angle.cos() + bitangent * angle.sin()) * radius;
+                    image.splat(direction + offset, ring.extend(0.0));
+                }
+            }
+        }
+        image.into_texture()
+    }
+}
+
+impl Default for StarCubemap {
+    fn default() -> Self {
+        Self {
+            size: 1024,
+            limiting_magnitude: 6.5,
+            reference_magnitude: 1.0,
+            orientation: Quat::IDENTITY,
+            background: Color::BLACK,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hyg_csv() {
+        // A trimmed-down excerpt of the HYG database, with a quoted header, the sun, a star with
+        // no color index, a row missing its magnitude, and a row which isn't a star at all.
+        let csv = r#""id","proper","ra","dec","mag","ci"
+0,"Sol",0.000000,0.000000,-26.700,0.656
+32263,"Sirius",6.752481,-16.716116,-1.440,0.009
+1,"",0.000060,1.089009,9.100,
+2,"",0.000283,-19.498840,,0.999
+not,a,star,at,all,
+"#;
+        let stars = Star::parse_hyg_csv(csv);
+        assert_eq!(
+            stars,
+            vec![
+                Star {
+                    right_ascension: 6.752481,
+                    declination: -16.716116,
+                    magnitude: -1.44,
+                    color_index: 0.009,
+                },
+                Star {
+                    right_ascension: 0.00006,
+                    declination: 1.089009,
+                    magnitude: 9.1,
+                    color_index: 0.0,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_hyg_csv_without_color_index_column() {
+        let stars = Star::parse_hyg_csv("ra,dec,mag\n6.752481,-16.716116,-1.44\n");
+        assert_eq!(stars.len(), 1);
+        assert_eq!(stars[0].color_index, 0.0);
+    }
+
+    #[test]
+    fn rejects_csv_without_required_columns() {
+        assert!(Star::parse_hyg_csv("").is_empty());
+        assert!(Star::parse_hyg_csv("ra,dec\n6.75,-16.7\n").is_empty());
+    }
+}
@@ -0,0 +1,482 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Crossfading between skybox materials.
+
+use bevy::ecs::schedule::SystemLabel;
+use bevy::prelude::*;
+
+use crate::day_night::lerp_color;
+use crate::SkyboxMaterial;
+
+/// Component which crossfades a skybox to a different material.
+///
+/// When this is added to a skybox entity, the [`SkyboxPlugin`](crate::SkyboxPlugin) fades from the
+/// skybox's current material to `to` over `duration` seconds. During the fade the skybox uses a
+/// temporary material which blends the textures of both skies using
+/// [`blend_weights`](SkyboxMaterial::blend_weights), and interpolates their colors and other
+/// numeric settings, such as the haze, ground, face tints, horizon offset, color temperature, and
+/// hue shift. Once the fade is complete, the skybox's material handle is replaced with `to` and
+/// this component is removed.
+///
+/// The fade starts once both materials are loaded. Some settings can't be interpolated, and switch
+/// from the old material's to the new one's halfway through the fade, so they pop:
+///
+/// - [`layer`](SkyboxMaterial::layer), which applies to every texture at once, and
+///   [`lower_texture`](SkyboxMaterial::lower_texture).
+/// - [`hidden_faces`](SkyboxMaterial::hidden_faces).
+/// - The optional effects, such as [`precipitation`](SkyboxMaterial::precipitation) and
+///   [`underwater`](SkyboxMaterial::underwater).
+///
+/// The material only has room for four textures, so when the two skies use more than that
+/// between them, such as a material blending four skies with
+/// [`blend_weights`](SkyboxMaterial::blend_weights), each is represented by whichever of its
+/// textures it is showing the most of, and pops slightly at the start and end of the fade. This
+/// also applies when inserting a new transition while one is in progress, if the interrupted fade
+/// is between skies which are themselves blending.
+///
+/// Settings that are usually animated by other components on the skybox, such as the sun, moon,
+/// sky discs, lightning, and meteors, are left to those components, which animate the temporary
+/// material during the fade. A [`DayNightCycle`](crate::DayNightCycle) instead keeps animating
+/// `to`, and the fade follows it, so the sky doesn't jump when the fade completes.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxMaterial, SkyboxTransition};
+/// # fn go_indoors(
+/// #     mut commands: Commands,
+/// #     skybox: Entity,
+/// #     indoor_sky: Handle<SkyboxMaterial>,
+/// # ) {
+/// commands
+///     .entity(skybox)
+///     .insert(SkyboxTransition::new(indoor_sky, 2.0));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SkyboxTransition {
+    /// Material to fade to.
+    pub to: Handle<SkyboxMaterial>,
+    /// How long the fade takes, in seconds.
+    pub duration: f32,
+    /// How long the fade has been running, in seconds.
+    elapsed: f32,
+    /// State of the fade once it has started.
+    fade: Option<Fade>,
+}
+
+impl SkyboxTransition {
+    /// Creates a transition that fades to `to` over `duration` seconds.
+    pub fn new(to: Handle<SkyboxMaterial>, duration: f32) -> Self {
+        Self {
+            to,
+            duration,
+            elapsed: 0.0,
+            fade: None,
+        }
+    }
+
+    /// How far along the fade is, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Temporary material used while a transition is fading.
+#[derive(Debug)]
+struct Fade {
+    /// The temporary material, which the skybox uses until the fade completes.
+    material: Handle<SkyboxMaterial>,
+    /// Settings of the material being faded from, as they were when the fade started.
+    from: SkyboxMaterial,
+    /// Settings of the material being faded to, kept up to date with it while the fade runs.
+    to: SkyboxMaterial,
+    /// Textures of the temporary material, in the order of its blend weights.
+    slots: Vec<Option<Handle<Texture>>>,
+    /// Weights of the temporary material's textures showing the old sky.
+    from_weights: Vec4,
+    /// Weights of the temporary material's textures showing the new sky.
+    to_weights: Vec4,
+}
+
+/// Gets the textures a material is showing, along with how much of each it shows. The weights add
+/// up to one. A missing texture is plain white, in the shader's weighted blend as elsewhere.
+fn texture_weights(material: &SkyboxMaterial) -> Vec<(Option<Handle<Texture>>, f32)> {
+    if let Some(weights) = material.blend_weights {
+        let weights = weights.max(Vec4::ZERO);
+        let total = weights.x + weights.y + weights.z + weights.w;
+        if total > 0.0 {
+            let textures = [
+                &material.texture,
+                &material.blend_texture,
+                &material.blend_texture_2,
+                &material.blend_texture_3,
+            ];
+            let weights: [f32; 4] = weights.into();
+            return textures
+                .iter()
+                .zip(weights.iter())
+                .filter(|(_, weight)| **weight > 0.0)
+                .map(|(texture, weight)| ((*texture).clone(), weight / total))
+                .collect();
+        }
+    }
+    if material.blend_texture.is_some() {
+        let factor = material.blend_factor.clamp(0.0, 1.0);
+        vec![
+            (material.texture.clone(), 1.0 - factor),
+            (material.blend_texture.clone(), factor),
+        ]
+    } else {
+        vec![(material.texture.clone(), 1.0)]
+    }
+}
+
+/// Gets the texture a material is mostly showing.
+fn dominant_texture(material: &SkyboxMaterial) -> (Option<Handle<Texture>>, f32) {
+    let mut textures = texture_weights(material);
+    let mut heaviest = 0;
+    for (i, (_, weight)) in textures.iter().enumerate() {
+        if *weight > textures[heaviest].1 {
+            heaviest = i;
+        }
+    }
+    (textures.swap_remove(heaviest).0, 1.0)
+}
+
+/// Adds `textures` to the texture slots of the temporary material, returning their weights in it,
+/// or `None` if there aren't enough slots left.
+fn fill_slots(
+    slots: &mut Vec<Option<Handle<Texture>>>,
+    textures: &[(Option<Handle<Texture>>, f32)],
+) -> Option<Vec4> {
+    let mut weights = [0.0; 4];
+    for (texture, weight) in textures {
+        let slot = match slots.iter().position(|slot| slot == texture) {
+            Some(slot) => slot,
+            None if slots.len() < weights.len() => {
+                slots.push(texture.clone());
+                slots.len() - 1
+            }
+            None => return None,
+        };
+        weights[slot] += weight;
+    }
+    Some(weights.into())
+}
+
+/// Builds the [`Fade`] and temporary material when a transition starts.
+fn start_fade(from: &SkyboxMaterial, to: &SkyboxMaterial) -> (Fade, SkyboxMaterial) {
+    let mut slots = Vec::new();
+    let mut weights = fill_slots(&mut slots, &texture_weights(from))
+        .zip(fill_slots(&mut slots, &texture_weights(to)));
+    if weights.is_none() {
+        // Too many textures between the two skies, so fall back to the main one of each.
+        slots.clear();
+        weights = fill_slots(&mut slots, &[dominant_texture(from)])
+            .zip(fill_slots(&mut slots, &[dominant_texture(to)]));
+    }
+    let (from_weights, to_weights) = weights.unwrap();
+    let mut textures = slots.clone();
+    textures.resize(4, None);
+    let material = SkyboxMaterial {
+        texture: textures[0].clone(),
+        blend_texture: textures[1].clone(),
+        blend_texture_2: textures[2].clone(),
+        blend_texture_3: textures[3].clone(),
+        blend_weights: Some(from_weights),
+        ..from.clone()
+    };
+    let fade = Fade {
+        material: Default::default(),
+        from: from.clone(),
+        to: to.clone(),
+        slots,
+        from_weights,
+        to_weights,
+    };
+    (fade, material)
+}
+
+impl Fade {
+    /// Follows changes to the material being faded to, such as from a
+    /// [`DayNightCycle`](crate::DayNightCycle) animating it, so the fade ends on exactly what the
+    /// material shows. Its textures keep their weights from before if they no longer fit in the
+    /// temporary material.
+    fn retarget(&mut self, to: &SkyboxMaterial) {
+        let mut slots = self.slots.clone();
+        if let Some(to_weights) = fill_slots(&mut slots, &texture_weights(to)) {
+            if slots.len() == self.slots.len() {
+                self.to_weights = to_weights;
+            }
+        }
+        self.to = to.clone();
+    }
+
+    /// Sets the temporary material's settings for `progress` through the fade.
+    fn apply(&self, material: &mut SkyboxMaterial, progress: f32) {
+        let (from, to) = (&self.from, &self.to);
+        let lerp = |from: f32, to: f32| from + (to - from) * progress;
+        material.blend_weights = Some(self.from_weights.lerp(self.to_weights, progress));
+        material.color = lerp_color(from.color, to.color, progress);
+        material.lower_texture_softness =
+            lerp(from.lower_texture_softness, to.lower_texture_softness);
+        material.haze_color = lerp_color(from.haze_color, to.haze_color, progress);
+        material.haze_height = lerp(from.haze_height, to.haze_height);
+        material.haze_softness = lerp(from.haze_softness, to.haze_softness);
+        material.horizon_glow_color =
+            lerp_color(from.horizon_glow_color, to.horizon_glow_color, progress);
+        material.horizon_glow_spread = lerp(from.horizon_glow_spread, to.horizon_glow_spread);
+        material.horizon_glow_height = lerp(from.horizon_glow_height, to.horizon_glow_height);
+        material.ground_color = lerp_color(from.ground_color, to.ground_color, progress);
+        material.ground_softness = lerp(from.ground_softness, to.ground_softness);
+        material.face_tints = match (from.face_tints, to.face_tints) {
+            (None, None) => None,
+            (from_tints, to_tints) => {
+                let from_tints = from_tints.unwrap_or([Color::WHITE; 6]);
+                let to_tints = to_tints.unwrap_or([Color::WHITE; 6]);
+                let mut tints = [Color::WHITE; 6];
+                for (i, tint) in tints.iter_mut().enumerate() {
+                    *tint = lerp_color(from_tints[i], to_tints[i], progress);
+                }
+                Some(tints)
+            }
+        };
+        material.horizon_offset = lerp(from.horizon_offset, to.horizon_offset);
+        material.color_temperature = lerp(from.color_temperature, to.color_temperature);
+        material.hue_shift = lerp(from.hue_shift, to.hue_shift);
+
+        // The rest can't be interpolated, so switch halfway.
+        let nearest = if progress < 0.5 { from } else { to };
+        material.layer = nearest.layer;
+        material.lower_texture = nearest.lower_texture.clone();
+        material.hidden_faces = nearest.hidden_faces;
+        material.night_vision = nearest.night_vision.clone();
+        material.light_pollution = nearest.light_pollution.clone();
+        material.underwater = nearest.underwater.clone();
+        // Keep the scroll, which is animated on the temporary material while it fades.
+        let scroll = material.precipitation.as_ref().map(|p| p.scroll);
+        material.precipitation = nearest.precipitation.clone();
+        if let (Some(precipitation), Some(scroll)) = (&mut material.precipitation, scroll) {
+            precipitation.scroll = scroll;
+        }
+    }
+}
+
+/// Labels for ordering other systems relative to [`run_skybox_transitions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub(crate) enum TransitionSystem {
+    Run,
+}
+
+/// System which runs [`SkyboxTransition`]s.
+pub(crate) fn run_skybox_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut skyboxes: Query<(Entity, &mut SkyboxTransition, &mut Handle<SkyboxMaterial>)>,
+) {
+    for (entity, mut transition, mut handle) in skyboxes.iter_mut() {
+        if transition.fade.is_none() {
+            let (mut fade, material) =
+                match (materials.get(&*handle), materials.get(&transition.to)) {
+                    (Some(from), Some(to)) => start_fade(from, to),
+                    // Wait until both materials are available.
+                    _ => continue,
+                };
+            fade.material = materials.add(material);
+            *handle = fade.material.clone();
+            transition.fade = Some(fade);
+        } else {
+            transition.elapsed += time.delta_seconds();
+        }
+
+        let progress = transition.progress();
+        if progress >= 1.0 {
+            *handle = transition.to.clone();
+            // Dropping the transition also drops the temporary material.
+            commands.entity(entity).remove::<SkyboxTransition>();
+            continue;
+        }
+
+        let SkyboxTransition { to, fade, .. } = &mut *transition;
+        let fade = fade.as_mut().unwrap();
+        if let Some(to) = materials.get(&*to) {
+            fade.retarget(to);
+        }
+        if let Some(material) = materials.get_mut(&fade.material) {
+            fade.apply(material, progress);
+        }
+    }
+}
+
+/// Component which fades a skybox to a solid color and back, such as to black while a new area
+/// loads or to white for a flashbang.
+///
+/// When this is added to a skybox entity, the [`SkyboxPlugin`](crate::SkyboxPlugin) fades the
+/// skybox's [`fade_color`](SkyboxMaterial::fade_color) in to `color` over `fade_in` seconds, holds
+/// it for `hold` seconds, then fades it back out over `fade_out` seconds and removes this
+/// component. The fade is done in the skybox shader, so it doesn't need any post-processing, but
+/// it only covers the sky. Like a [`SkyboxAnimation`](crate::SkyboxAnimation), it changes the
+/// skybox's material, so all skyboxes sharing the material fade with it.
+///
+/// To stay faded until something finishes, such as a scene load, set `hold` to `f32::INFINITY`
+/// and call [`release`](Self::release) when it's done.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{FadeEasing, SkyboxColorFade};
+/// # fn flashbang(mut commands: Commands, skybox: Entity) {
+/// commands.entity(skybox).insert(
+///     SkyboxColorFade::new(Color::WHITE, 0.05, 2.0)
+///         .with_hold(0.5)
+///         .with_easing(FadeEasing::EaseIn),
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkyboxColorFade {
+    /// Color to fade to. Its alpha is multiplied into how far the sky is faded, so a translucent
+    /// color only fades partway.
+    pub color: Color,
+    /// How long fading to the color takes, in seconds.
+    pub fade_in: f32,
+    /// How long to hold the color before fading back, in seconds.
+    pub hold: f32,
+    /// How long fading back from the color takes, in seconds.
+    pub fade_out: f32,
+    /// How the fade speeds up and slows down.
+    pub easing: FadeEasing,
+    /// How long the fade has been running, in seconds.
+    elapsed: f32,
+}
+
+impl SkyboxColorFade {
+    /// Creates a fade to `color` over `fade_in` seconds and back over `fade_out` seconds, with no
+    /// hold in between.
+    pub fn new(color: Color, fade_in: f32, fade_out: f32) -> Self {
+        Self {
+            color,
+            fade_in,
+            hold: 0.0,
+            fade_out,
+            easing: FadeEasing::default(),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Holds the color for `hold` seconds before fading back.
+    pub fn with_hold(mut self, hold: f32) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Uses `easing` for the fade.
+    pub fn with_easing(mut self, easing: FadeEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Ends the hold, so the sky starts fading back now if it is fully faded. Has no effect while
+    /// fading in or out.
+    pub fn release(&mut self) {
+        if self.elapsed >= self.fade_in {
+            self.hold = self.hold.min(self.elapsed - self.fade_in);
+        }
+    }
+
+    /// How far the sky is faded to the color, from `0.0` to `1.0`, with easing applied.
+    pub fn amount(&self) -> f32 {
+        let out_start = self.fade_in + self.hold;
+        let linear = if self.elapsed < self.fade_in {
+            self.elapsed / self.fade_in
+        } else if self.elapsed < out_start {
+            1.0
+        } else if self.fade_out > 0.0 {
+            1.0 - (self.elapsed - out_start) / self.fade_out
+        } else {
+            0.0
+        };
+        self.easing.apply(linear.clamp(0.0, 1.0))
+    }
+
+    /// Whether the sky has faded back from the color.
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.fade_in + self.hold + self.fade_out
+    }
+}
+
+/// How a [`SkyboxColorFade`] speeds up and slows down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FadeEasing {
+    /// Fades at a constant speed.
+    Linear,
+    /// Starts slowly and speeds up towards the color, and likewise speeds up away from it when
+    /// fading back, so the sky lingers near its normal look.
+    EaseIn,
+    /// Starts quickly and slows down towards the color, so the sky lingers near the color.
+    EaseOut,
+    /// Starts and ends slowly, so there's no visible jolt at either end. This is the default.
+    EaseInOut,
+}
+
+// Deriving this needs `#[default]`, which is newer than the Rust versions Bevy 0.5 supports.
+#[allow(clippy::derivable_impls)]
+impl Default for FadeEasing {
+    fn default() -> Self {
+        FadeEasing::EaseInOut
+    }
+}
+
+impl FadeEasing {
+    /// Applies the easing to `t`, from `0.0` (the normal sky) to `1.0` (the color).
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            FadeEasing::Linear => t,
+            FadeEasing::EaseIn => t * t,
+            FadeEasing::EaseOut => t * (2.0 - t),
+            FadeEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// System which runs [`SkyboxColorFade`]s.
+pub(crate) fn run_color_fades(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    mut skyboxes: Query<(Entity, &mut SkyboxColorFade, &Handle<SkyboxMaterial>)>,
+) {
+    for (entity, mut fade, handle) in skyboxes.iter_mut() {
+        let material = match materials.get_mut(handle) {
+            Some(material) => material,
+            // Wait until the material is available.
+            None => continue,
+        };
+        fade.elapsed += time.delta_seconds();
+        if fade.is_finished() {
+            material.fade_color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+            commands.entity(entity).remove::<SkyboxColorFade>();
+            continue;
+        }
+        let mut color = fade.color;
+        color.set_a(color.a() * fade.amount());
+        material.fade_color = color;
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Drawing the sky as seen from under water.
+
+use bevy::prelude::*;
+use bevy::render::camera::ActiveCameras;
+use bevy::render::render_graph::base::camera::CAMERA_3D;
+
+use crate::SkyboxMaterial;
+
+/// Settings for drawing a sky as seen from under water, set on [`SkyboxMaterial::underwater`].
+///
+/// From under a calm water surface, the whole sky is squeezed into Snell's window, a cone around
+/// straight up whose half-angle depends on the water's refractive index (about 49° for water).
+/// Outside of it, only the water itself is seen. The sky's effects, such as the sun's disc, are
+/// refracted along with it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Underwater {
+    /// Refractive index of the water, relative to the air above it. `1.33` is fresh water.
+    pub refractive_index: f32,
+    /// Angle in radians inside the edge of Snell's window over which the sky fades into the
+    /// water. A perfectly sharp edge looks unnatural, since real water surfaces always ripple.
+    pub edge_softness: f32,
+    /// Color multiplied with the sky seen through Snell's window. Deep or murky water darkens and
+    /// tints the sky above.
+    pub tint: Color,
+    /// Color of the water outside Snell's window.
+    pub water_color: Color,
+}
+
+impl Default for Underwater {
+    fn default() -> Self {
+        Self {
+            refractive_index: 1.33,
+            edge_softness: 0.1,
+            tint: Color::rgb(0.6, 0.8, 0.85),
+            water_color: Color::rgb(0.0, 0.12, 0.18),
+        }
+    }
+}
+
+/// Component which switches a skybox to its underwater look while the active 3D camera is below
+/// `height`. The skybox's material has its [`underwater`](SkyboxMaterial::underwater) settings
+/// set to `underwater` below the water and cleared above it, so all skyboxes sharing the material
+/// change with it.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxWaterLevel, Underwater};
+/// # fn setup(mut commands: Commands) {
+/// commands
+///     .spawn_bundle(SkyboxBundle::default())
+///     .insert(SkyboxWaterLevel::new(0.0, Underwater::default()));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyboxWaterLevel {
+    /// World-space height of the water's surface.
+    pub height: f32,
+    /// How the sky looks while the camera is below the surface.
+    pub underwater: Underwater,
+}
+
+impl SkyboxWaterLevel {
+    /// Creates a `SkyboxWaterLevel` with the surface at `height`.
+    pub fn new(height: f32, underwater: Underwater) -> Self {
+        Self { height, underwater }
+    }
+}
+
+/// System which applies [`SkyboxWaterLevel`]s.
+pub(crate) fn apply_water_levels(
+    active_cameras: Res<ActiveCameras>,
+    cameras: Query<&GlobalTransform>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    skyboxes: Query<(&SkyboxWaterLevel, &Handle<SkyboxMaterial>)>,
+) {
+    let camera = active_cameras
+        .get(CAMERA_3D)
+        .and_then(|camera| camera.entity)
+        .and_then(|entity| cameras.get(entity).ok());
+    let camera_height = match camera {
+        Some(transform) => transform.translation.y,
+        None => return,
+    };
+    for (water_level, handle) in skyboxes.iter() {
+        let underwater = if camera_height < water_level.height {
+            Some(&water_level.underwater)
+        } else {
+            None
+        };
+        // Only touch the material when it changes, so it isn't uploaded again every frame.
+        let unchanged = match materials.get(handle) {
+            Some(material) => material.underwater.as_ref() == underwater,
+            None => continue,
+        };
+        if !unchanged {
+            if let Some(material) = materials.get_mut(handle) {
+                material.underwater = underwater.cloned();
+            }
+        }
+    }
+}
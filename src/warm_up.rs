@@ -0,0 +1,111 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compiling skybox pipelines ahead of time.
+
+use bevy::prelude::*;
+use bevy::render::pipeline::PipelineDescriptor;
+
+use crate::cube::{srgb8, srgb8_texture, FACES};
+use crate::{SkyboxBundle, SkyboxMaterial, SKYBOX_PIPELINE_HANDLE};
+
+/// How many frames warm-up skyboxes are kept around. Bevy sets up a mesh's vertex layout for its
+/// pipelines a frame after it's spawned, so the pipelines compiled on the first frame may not be
+/// the ones real skyboxes use.
+const WARM_UP_FRAMES: u32 = 3;
+
+/// Resource listing extra skybox pipelines for [`warm_up_skybox_pipelines`] to compile, such as
+/// those from [`skybox_pipeline_with_blend_mode`](crate::skybox_pipeline_with_blend_mode). The
+/// default skybox pipeline is always compiled, so this is empty by default.
+#[derive(Debug, Clone, Default)]
+pub struct SkyboxWarmUpPipelines(pub Vec<Handle<PipelineDescriptor>>);
+
+/// Marker for the invisible skyboxes spawned by [`warm_up_skybox_pipelines`], which counts the
+/// frames until they are despawned.
+pub(crate) struct SkyboxWarmUp {
+    frames: u32,
+}
+
+/// System which compiles the skybox pipelines ahead of time, so the first frame a skybox appears
+/// doesn't hitch while its shaders compile. Bevy only compiles a pipeline the first time
+/// something is drawn with it, and compiles a separate variant for each combination of the
+/// [`SkyboxMaterial`]'s textures that are set and each MSAA sample count, so a game which fades to
+/// a textured sky after starting with a color can hitch more than once.
+///
+/// This spawns a skybox for every combination of textures, with the default pipeline and each of
+/// the [`SkyboxWarmUpPipelines`], which hides all of its faces so nothing is drawn. They are
+/// despawned a few frames later, once they've been drawn with the current [`Msaa`] sample count.
+/// Run it once during a loading screen, for example as a startup system, and again if the sample
+/// count changes.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{warm_up_skybox_pipelines, SkyboxPlugin};
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     .add_startup_system(warm_up_skybox_pipelines.system())
+///     .run();
+/// ```
+pub fn warm_up_skybox_pipelines(
+    mut commands: Commands,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<SkyboxMaterial>>,
+    pipelines: Res<SkyboxWarmUpPipelines>,
+) {
+    let black = srgb8(Color::BLACK);
+    let texture = textures.add(srgb8_texture(1, black.repeat(FACES)));
+    let pipelines = std::iter::once(SKYBOX_PIPELINE_HANDLE.typed()).chain(pipelines.0.clone());
+    for pipeline in pipelines {
+        // Each of the five textures adds its own shader def, so every combination of them is a
+        // separate variant.
+        for set in 0..32u32 {
+            let texture = |bit: u32| {
+                if set & (1 << bit) != 0 {
+                    Some(texture.clone())
+                } else {
+                    None
+                }
+            };
+            let material = materials.add(SkyboxMaterial {
+                texture: texture(0),
+                blend_texture: texture(1),
+                blend_texture_2: texture(2),
+                blend_texture_3: texture(3),
+                lower_texture: texture(4),
+                hidden_faces: (1 << FACES) - 1,
+                ..Default::default()
+            });
+            commands
+                .spawn_bundle(SkyboxBundle::new(material).with_pipeline(pipeline.clone()))
+                .insert(SkyboxWarmUp {
+                    frames: WARM_UP_FRAMES,
+                });
+        }
+    }
+}
+
+/// System which despawns warm-up skyboxes once they've been drawn.
+pub(crate) fn finish_warm_ups(
+    mut commands: Commands,
+    mut skyboxes: Query<(Entity, &mut SkyboxWarmUp)>,
+) {
+    for (entity, mut warm_up) in skyboxes.iter_mut() {
+        if warm_up.frames == 0 {
+            commands.entity(entity).despawn();
+        } else {
+            warm_up.frames -= 1;
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Switching skies based on the weather.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::settings::skyboxes_enabled;
+use crate::{SkyboxMaterial, SkyboxSystem, SkyboxTransition};
+
+/// Optional plugin which switches skies when the weather changes. Adds the [`SkyboxWeather`]
+/// resource and a system which fades every skybox with a [`WeatherSkybox`] component to the sky
+/// registered for the current weather, using a [`SkyboxTransition`].
+///
+/// This plugin only drives the weather; [`SkyboxPlugin`](crate::SkyboxPlugin) must be added too.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{
+/// #     SkyboxBundle, SkyboxMaterial, SkyboxPlugin, SkyboxWeather, SkyboxWeatherPlugin, Weather,
+/// #     WeatherSkybox,
+/// # };
+/// App::build()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(SkyboxPlugin)
+///     .add_plugin(SkyboxWeatherPlugin)
+///     .add_startup_system(setup.system())
+///     .add_system(start_storm.system());
+///
+/// fn setup(
+///     mut commands: Commands,
+///     mut skyboxes: ResMut<Assets<SkyboxMaterial>>,
+///     mut weather: ResMut<SkyboxWeather>,
+/// ) {
+///     weather
+///         .register(Weather::Clear, skyboxes.add(SkyboxMaterial::from_color(Color::CYAN)), 10.0)
+///         .register(Weather::Storm, skyboxes.add(SkyboxMaterial::from_color(Color::GRAY)), 3.0);
+///     commands
+///         .spawn_bundle(SkyboxBundle::default())
+///         .insert(WeatherSkybox);
+/// }
+///
+/// fn start_storm(keys: Res<Input<KeyCode>>, mut weather: ResMut<SkyboxWeather>) {
+///     if keys.just_pressed(KeyCode::S) {
+///         weather.set(Weather::Storm);
+///     }
+/// }
+/// ```
+pub struct SkyboxWeatherPlugin;
+
+impl Plugin for SkyboxWeatherPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<SkyboxWeather>().add_system(
+            apply_weather
+                .system()
+                .label(SkyboxSystem::Animate)
+                .with_run_criteria(skyboxes_enabled.system()),
+        );
+    }
+}
+
+/// Weather states which can have skies registered in [`SkyboxWeather`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weather {
+    /// Clear skies, with little or no cloud.
+    Clear,
+    /// A sky covered in cloud, without any rain.
+    Overcast,
+    /// Rain falling from a cloudy sky.
+    Rain,
+    /// Heavy rain from dark clouds, usually with lightning.
+    Storm,
+    /// Snow falling from a cloudy sky.
+    Snow,
+    /// Fog hiding most of the sky.
+    Fog,
+    /// Any other weather your game needs, identified by a number of your choosing.
+    Custom(u32),
+}
+
+/// Sky registered for a weather state.
+#[derive(Debug, Clone)]
+pub struct WeatherSky {
+    /// Material to show during this weather.
+    pub material: Handle<SkyboxMaterial>,
+    /// How long, in seconds, it takes to fade to this sky when the weather changes to this state.
+    pub transition_time: f32,
+}
+
+/// Resource holding the current weather and the skies registered for each weather state.
+///
+/// When the weather changes, every skybox with a [`WeatherSkybox`] component fades to the sky
+/// registered for the new weather, taking that sky's `transition_time`. Skyboxes spawned later
+/// switch to the current weather's sky immediately. If no sky is registered for the current weather,
+/// skyboxes keep whatever sky they had.
+#[derive(Debug)]
+pub struct SkyboxWeather {
+    current: Weather,
+    skies: HashMap<Weather, WeatherSky>,
+}
+
+impl Default for SkyboxWeather {
+    /// Starts with clear weather and no registered skies.
+    fn default() -> Self {
+        Self {
+            current: Weather::Clear,
+            skies: Default::default(),
+        }
+    }
+}
+
+impl SkyboxWeather {
+    /// Registers the sky to show for `weather`, and how many seconds it takes to fade to it.
+    /// Replaces any sky previously registered for the same weather.
+    pub fn register(
+        &mut self,
+        weather: Weather,
+        material: Handle<SkyboxMaterial>,
+        transition_time: f32,
+    ) -> &mut Self {
+        self.skies.insert(
+            weather,
+            WeatherSky {
+                material,
+                transition_time,
+            },
+        );
+        self
+    }
+
+    /// Removes the sky registered for `weather`, returning it if there was one.
+    pub fn unregister(&mut self, weather: Weather) -> Option<WeatherSky> {
+        self.skies.remove(&weather)
+    }
+
+    /// Gets the sky registered for `weather`.
+    pub fn sky(&self, weather: Weather) -> Option<&WeatherSky> {
+        self.skies.get(&weather)
+    }
+
+    /// Changes the current weather.
+    pub fn set(&mut self, weather: Weather) {
+        self.current = weather;
+    }
+
+    /// Gets the current weather.
+    pub fn current(&self) -> Weather {
+        self.current
+    }
+}
+
+/// Marker for skybox entities that should follow the [`SkyboxWeather`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeatherSkybox;
+
+/// System which fades skyboxes to the sky for the current weather.
+#[allow(clippy::type_complexity)]
+fn apply_weather(
+    mut commands: Commands,
+    weather: Res<SkyboxWeather>,
+    mut skyboxes: Query<(
+        Entity,
+        &mut Handle<SkyboxMaterial>,
+        Option<&SkyboxTransition>,
+        ChangeTrackers<WeatherSkybox>,
+    )>,
+) {
+    let sky = match weather.sky(weather.current()) {
+        Some(sky) => sky,
+        None => return,
+    };
+    for (entity, mut handle, transition, tracker) in skyboxes.iter_mut() {
+        if tracker.is_added() {
+            // New skyboxes start out with the right sky rather than fading to it.
+            if *handle != sky.material {
+                *handle = sky.material.clone();
+            }
+            continue;
+        }
+        if !weather.is_changed() {
+            continue;
+        }
+        let already_fading = matches!(transition, Some(t) if t.to == sky.material);
+        if *handle != sky.material && !already_fading {
+            commands.entity(entity).insert(SkyboxTransition::new(
+                sky.material.clone(),
+                sky.transition_time,
+            ));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wind shared by everything that moves across the sky.
+
+use bevy::prelude::*;
+
+/// Resource with the wind blowing across the sky, so everything the wind moves stays in step.
+/// While it's present, the [`SkyboxPlugin`](crate::SkyboxPlugin) sets the `wind` of every
+/// material's [`Precipitation`](crate::Precipitation) from it, and gameplay such as sailing or
+/// gliding can read the same values. Without it, each `Precipitation` keeps its own `wind`.
+///
+/// Precipitation is the only part of the sky the wind moves. The crate has no separate cloud or
+/// aurora layers to scroll: clouds are part of the sky texture, such as those baked by
+/// [`CloudCubemaps`](crate::CloudCubemaps), and can only be turned as a whole with a
+/// [`SkyboxRotation`](crate::SkyboxRotation), which doesn't follow the wind.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::SkyWind;
+/// # struct Boat;
+/// fn setup(mut commands: Commands) {
+///     // A breeze blowing towards the east.
+///     commands.insert_resource(SkyWind::new(Vec3::X, 4.0));
+/// }
+///
+/// fn sail(wind: Res<SkyWind>, mut boats: Query<&mut Transform, With<Boat>>, time: Res<Time>) {
+///     for mut transform in boats.iter_mut() {
+///         transform.translation += wind.velocity() * 0.1 * time.delta_seconds();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkyWind {
+    /// World-space direction the wind blows towards. Only its horizontal part is used, and it
+    /// doesn't need to be normalized.
+    pub direction: Vec3,
+    /// Speed of the wind in meters per second.
+    pub speed: f32,
+}
+
+impl SkyWind {
+    /// Creates a wind blowing towards `direction` at `speed` meters per second.
+    pub fn new(direction: Vec3, speed: f32) -> Self {
+        Self { direction, speed }
+    }
+
+    /// World-space velocity of the wind in meters per second, which is always horizontal.
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::new(self.direction.x, 0.0, self.direction.z).normalize_or_zero() * self.speed
+    }
+}
+
+impl Default for SkyWind {
+    /// Still air.
+    fn default() -> Self {
+        Self::new(Vec3::X, 0.0)
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2021 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Switching skies when the camera moves between areas of the world.
+
+use bevy::prelude::*;
+use bevy::render::camera::ActiveCameras;
+use bevy::render::render_graph::base::camera::CAMERA_3D;
+
+use crate::{SkyboxMaterial, SkyboxTransition};
+
+/// Component which gives an area of the world its own sky, such as a biome in an open-world game.
+///
+/// The zone covers `shape` around the entity's `GlobalTransform`, so it can be moved, rotated, and
+/// scaled with the entity. When the active 3D camera moves into the zone, every skybox with a
+/// [`ZoneSkybox`] component fades to the zone's `material` over `transition_time` seconds, using
+/// a [`SkyboxTransition`]. Where zones overlap, the one with the highest `priority` wins. Once the
+/// camera leaves every zone the skyboxes keep the last zone's sky, so a huge zone with a low
+/// priority around the whole world can be used as the default sky.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_skybox_cubemap::{SkyboxBundle, SkyboxMaterial, SkyboxZone, ZoneShape, ZoneSkybox};
+/// # fn setup(mut commands: Commands, mut skyboxes: ResMut<Assets<SkyboxMaterial>>) {
+/// let desert = skyboxes.add(SkyboxMaterial::from_color(Color::ORANGE));
+/// let forest = skyboxes.add(SkyboxMaterial::from_color(Color::DARK_GREEN));
+/// commands.spawn_bundle(SkyboxBundle::default()).insert(ZoneSkybox);
+/// commands
+///     .spawn_bundle((Transform::from_xyz(500.0, 0.0, 0.0), GlobalTransform::default()))
+///     .insert(SkyboxZone::new(
+///         ZoneShape::Box {
+///             half_extents: Vec3::new(400.0, 1000.0, 400.0),
+///         },
+///         desert,
+///         5.0,
+///     ));
+/// commands
+///     .spawn_bundle((Transform::from_xyz(-500.0, 0.0, 0.0), GlobalTransform::default()))
+///     .insert(SkyboxZone::new(ZoneShape::Sphere { radius: 400.0 }, forest, 5.0));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkyboxZone {
+    /// Area the zone covers, in the entity's local space.
+    pub shape: ZoneShape,
+    /// Material to show while the camera is in the zone.
+    pub material: Handle<SkyboxMaterial>,
+    /// How long, in seconds, it takes to fade to this zone's sky when the camera enters it.
+    pub transition_time: f32,
+    /// Which zone wins where zones overlap. Higher priorities win. Zones with the same priority are
+    /// picked between arbitrarily.
+    pub priority: i32,
+}
+
+impl SkyboxZone {
+    /// Creates a zone covering `shape` which fades to `material` over `transition_time` seconds
+    /// when the camera enters it, with a priority of `0`.
+    pub fn new(shape: ZoneShape, material: Handle<SkyboxMaterial>, transition_time: f32) -> Self {
+        Self {
+            shape,
+            material,
+            transition_time,
+            priority: 0,
+        }
+    }
+
+    /// Sets the zone's priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Whether `point`, in world space, is in the zone placed at `transform`.
+    pub fn contains(&self, transform: &GlobalTransform, point: Vec3) -> bool {
+        let local = transform.compute_matrix().inverse().transform_point3(point);
+        match self.shape {
+            ZoneShape::Box { half_extents } => local.abs().cmple(half_extents).all(),
+            ZoneShape::Sphere { radius } => local.length() <= radius,
+        }
+    }
+}
+
+/// Shape of the area covered by a [`SkyboxZone`], centered on its entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZoneShape {
+    /// Box reaching `half_extents` from the center along each axis.
+    Box { half_extents: Vec3 },
+    /// Sphere with the given radius.
+    Sphere { radius: f32 },
+}
+
+/// Marker for skybox entities that should switch to the sky of the [`SkyboxZone`] the camera is
+/// in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZoneSkybox;
+
+/// System which fades skyboxes to the sky of the zone the camera is in.
+#[allow(clippy::type_complexity)]
+pub(crate) fn switch_zone_skies(
+    mut commands: Commands,
+    mut current: Local<Option<Entity>>,
+    active_cameras: Res<ActiveCameras>,
+    cameras: Query<&GlobalTransform>,
+    zones: Query<(Entity, &SkyboxZone, &GlobalTransform)>,
+    mut skyboxes: Query<(
+        Entity,
+        &mut Handle<SkyboxMaterial>,
+        Option<&SkyboxTransition>,
+        ChangeTrackers<ZoneSkybox>,
+    )>,
+) {
+    let camera = active_cameras
+        .get(CAMERA_3D)
+        .and_then(|camera| camera.entity)
+        .and_then(|entity| cameras.get(entity).ok());
+    let position = match camera {
+        Some(transform) => transform.translation,
+        None => return,
+    };
+    let mut entered = None;
+    for (entity, zone, transform) in zones.iter() {
+        if !zone.contains(transform, position) {
+            continue;
+        }
+        if !matches!(entered, Some((_, priority)) if priority >= zone.priority) {
+            entered = Some((entity, zone.priority));
+        }
+    }
+    let changed = match entered {
+        Some((entity, _)) if *current != Some(entity) => {
+            *current = Some(entity);
+            true
+        }
+        _ => false,
+    };
+
+    let zone = match current.and_then(|entity| zones.get(entity).ok()) {
+        Some((_, zone, _)) => zone,
+        None => return,
+    };
+    for (entity, mut handle, transition, tracker) in skyboxes.iter_mut() {
+        if tracker.is_added() {
+            // New skyboxes start out with the right sky rather than fading to it.
+            if *handle != zone.material {
+                *handle = zone.material.clone();
+            }
+            continue;
+        }
+        if !changed {
+            continue;
+        }
+        let already_fading = matches!(transition, Some(t) if t.to == zone.material);
+        if *handle != zone.material && !already_fading {
+            commands.entity(entity).insert(SkyboxTransition::new(
+                zone.material.clone(),
+                zone.transition_time,
+            ));
+        }
+    }
+}